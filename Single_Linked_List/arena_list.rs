@@ -0,0 +1,301 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+const NIL: u32 = u32::MAX;
+
+enum Slot<T> {
+    Occupied { elem: T, next: u32 },
+    Free { next_free: u32 },
+}
+
+pub struct ArenaList<T> {
+    nodes: Vec<Slot<T>>,
+    head: u32,
+    free: u32,
+    len: usize,
+}
+
+impl<T> ArenaList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaList {
+            nodes: Vec::with_capacity(capacity),
+            head: NIL,
+            free: NIL,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let slot = Slot::Occupied {
+            elem,
+            next: self.head,
+        };
+
+        if self.free != NIL {
+            let index = self.free;
+            match self.nodes[index as usize] {
+                Slot::Free { next_free } => self.free = next_free,
+                Slot::Occupied { .. } => unreachable!("free list points at a live node"),
+            }
+            self.nodes[index as usize] = slot;
+            self.head = index;
+        } else {
+            assert!(self.nodes.len() < NIL as usize, "arena is full");
+            self.head = self.nodes.len() as u32;
+            self.nodes.push(slot);
+        }
+
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.head == NIL {
+            return None;
+        }
+
+        let index = self.head;
+        let slot = std::mem::replace(
+            &mut self.nodes[index as usize],
+            Slot::Free {
+                next_free: self.free,
+            },
+        );
+        self.free = index;
+        self.len -= 1;
+
+        match slot {
+            Slot::Occupied { elem, next } => {
+                self.head = next;
+                Some(elem)
+            }
+            Slot::Free { .. } => unreachable!("head points at a free slot"),
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        match self.nodes.get(self.head as usize) {
+            Some(Slot::Occupied { elem, .. }) => Some(elem),
+            _ => None,
+        }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        match self.nodes.get_mut(self.head as usize) {
+            Some(Slot::Occupied { elem, .. }) => Some(elem),
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.head = NIL;
+        self.free = NIL;
+        self.len = 0;
+    }
+
+    // Moves the live nodes into a dense array in list order and drops the
+    // free slots, so iteration walks memory front to back again.
+    pub fn compact(&mut self) {
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut cur = self.head;
+
+        while cur != NIL {
+            let slot =
+                std::mem::replace(&mut self.nodes[cur as usize], Slot::Free { next_free: NIL });
+            match slot {
+                Slot::Occupied { elem, next } => {
+                    let index = nodes.len() as u32;
+                    let next_index = if next == NIL { NIL } else { index + 1 };
+                    nodes.push(Slot::Occupied {
+                        elem,
+                        next: next_index,
+                    });
+                    cur = next;
+                }
+                Slot::Free { .. } => unreachable!("list links into a free slot"),
+            }
+        }
+
+        self.head = if nodes.is_empty() { NIL } else { 0 };
+        self.free = NIL;
+        self.nodes = nodes;
+    }
+}
+
+impl<T> Default for ArenaList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct IntoIter<T>(ArenaList<T>);
+
+impl<T> IntoIterator for ArenaList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+pub struct Iter<'a, T> {
+    nodes: &'a [Slot<T>],
+    next: u32,
+    len: usize,
+}
+
+impl<T> ArenaList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            nodes: &self.nodes,
+            next: self.head,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.nodes.get(self.next as usize) {
+            Some(Slot::Occupied { elem, next }) => {
+                self.next = *next;
+                self.len -= 1;
+                Some(elem)
+            }
+            _ => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    nodes: *mut Slot<T>,
+    next: u32,
+    len: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> ArenaList<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            nodes: self.nodes.as_mut_ptr(),
+            next: self.head,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArenaList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == NIL {
+            return None;
+        }
+
+        // every slot is reached at most once, so the borrows never overlap
+        match unsafe { &mut *self.nodes.add(self.next as usize) } {
+            Slot::Occupied { elem, next } => {
+                self.next = *next;
+                self.len -= 1;
+                Some(elem)
+            }
+            Slot::Free { .. } => unreachable!("list links into a free slot"),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
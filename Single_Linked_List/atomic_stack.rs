@@ -0,0 +1,198 @@
+use std::cell::UnsafeCell;
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering::*};
+
+const SCAN_THRESHOLD: usize = 64;
+
+// Same shape as `first::Node`; `elem` is moved out by `pop` long before the
+// node itself can be freed, hence `ManuallyDrop`.
+struct Node<T> {
+    elem: ManuallyDrop<T>,
+    next: *mut Node<T>,
+}
+
+// A hazard record is owned by one thread for the duration of a `pop`.
+// Nodes are only freed once no record protects them, which rules out both
+// use-after-free and ABA on `head`.
+struct HazardRecord<T> {
+    hazard: AtomicPtr<Node<T>>,
+    active: AtomicBool,
+    next: *mut HazardRecord<T>,
+    retired: UnsafeCell<Vec<*mut Node<T>>>,
+}
+
+pub struct AtomicStack<T> {
+    head: AtomicPtr<Node<T>>,
+    records: AtomicPtr<HazardRecord<T>>,
+}
+
+struct Guard<'a, T> {
+    stack: &'a AtomicStack<T>,
+    record: &'a HazardRecord<T>,
+}
+
+impl<T> AtomicStack<T> {
+    pub fn new() -> Self {
+        AtomicStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            records: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: ptr::null_mut(),
+        }));
+
+        let mut head = self.head.load(Relaxed);
+        loop {
+            unsafe { (*node).next = head };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Release, Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = self.protect();
+        loop {
+            let head = self.head.load(Acquire);
+            if head.is_null() {
+                return None;
+            }
+
+            guard.record.hazard.store(head, SeqCst);
+            if self.head.load(SeqCst) != head {
+                continue;
+            }
+
+            let next = unsafe { (*head).next };
+            if self
+                .head
+                .compare_exchange(head, next, SeqCst, Relaxed)
+                .is_ok()
+            {
+                guard.record.hazard.store(ptr::null_mut(), Release);
+                let elem = unsafe { ManuallyDrop::take(&mut (*head).elem) };
+                guard.retire(head);
+                return Some(elem);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Acquire).is_null()
+    }
+
+    fn protect(&self) -> Guard<'_, T> {
+        let mut cur = self.records.load(Acquire);
+        while let Some(record) = unsafe { cur.as_ref() } {
+            if !record.active.load(Relaxed)
+                && record
+                    .active
+                    .compare_exchange(false, true, Acquire, Relaxed)
+                    .is_ok()
+            {
+                return Guard {
+                    stack: self,
+                    record,
+                };
+            }
+            cur = record.next;
+        }
+
+        let record = Box::into_raw(Box::new(HazardRecord {
+            hazard: AtomicPtr::new(ptr::null_mut()),
+            active: AtomicBool::new(true),
+            next: ptr::null_mut(),
+            retired: UnsafeCell::new(Vec::new()),
+        }));
+        let mut head = self.records.load(Relaxed);
+        loop {
+            unsafe { (*record).next = head };
+            match self
+                .records
+                .compare_exchange_weak(head, record, Release, Relaxed)
+            {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+        Guard {
+            stack: self,
+            record: unsafe { &*record },
+        }
+    }
+}
+
+impl<T> Guard<'_, T> {
+    fn retire(&self, node: *mut Node<T>) {
+        // only the thread holding this record touches its retired list
+        let retired = unsafe { &mut *self.record.retired.get() };
+        retired.push(node);
+        if retired.len() < SCAN_THRESHOLD {
+            return;
+        }
+
+        let mut hazards = vec![];
+        let mut cur = self.stack.records.load(Acquire);
+        while let Some(record) = unsafe { cur.as_ref() } {
+            let hazard = record.hazard.load(SeqCst);
+            if !hazard.is_null() {
+                hazards.push(hazard);
+            }
+            cur = record.next;
+        }
+
+        retired.retain(|&node| {
+            if hazards.contains(&node) {
+                true
+            } else {
+                drop(unsafe { Box::from_raw(node) });
+                false
+            }
+        });
+    }
+}
+
+impl<T> Drop for Guard<'_, T> {
+    fn drop(&mut self) {
+        self.record.hazard.store(ptr::null_mut(), Release);
+        self.record.active.store(false, Release);
+    }
+}
+
+impl<T> Default for AtomicStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for AtomicStack<T> {
+    fn drop(&mut self) {
+        let mut cur = *self.head.get_mut();
+        while !cur.is_null() {
+            let mut node = unsafe { Box::from_raw(cur) };
+            unsafe { ManuallyDrop::drop(&mut node.elem) };
+            cur = node.next;
+        }
+
+        let mut cur = *self.records.get_mut();
+        while !cur.is_null() {
+            let record = unsafe { Box::from_raw(cur) };
+            for node in record.retired.into_inner() {
+                drop(unsafe { Box::from_raw(node) });
+            }
+            cur = record.next;
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for AtomicStack<T> {}
+unsafe impl<T: Send> Sync for AtomicStack<T> {}
@@ -1,6 +1,11 @@
-#[derive(Debug)]
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+
 pub struct List<T> {
     head: Link<T>,
+    len: usize,
 }
 
 #[derive(Debug)]
@@ -13,7 +18,7 @@ type Link<T> = Option<Box<Node<T>>>;
 
 impl<T> List<T> {
     pub fn new() -> List<T> {
-        List { head: None }
+        List { head: None, len: 0 }
     }
 
     pub fn push(&mut self, elem: T) {
@@ -23,11 +28,13 @@ impl<T> List<T> {
         });
 
         self.head = Some(new_node);
+        self.len += 1;
     }
 
     pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|node| {
             self.head = node.next;
+            self.len -= 1;
             node.elem
         })
     }
@@ -39,6 +46,197 @@ impl<T> List<T> {
     pub fn peek_mut(&mut self) -> Option<&mut T> {
         self.head.as_mut().map(|node| &mut node.elem)
     }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn reverse(&mut self) {
+        let mut prev: Link<T> = None;
+        let mut cur = self.head.take();
+
+        while let Some(mut node) = cur {
+            cur = node.next.take();
+            node.next = prev;
+            prev = Some(node);
+        }
+
+        self.head = prev;
+    }
+
+    pub fn append(&mut self, other: &mut List<T>) {
+        let mut tail = &mut self.head;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+
+        *tail = other.head.take();
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    pub fn split_off(&mut self, at: usize) -> List<T> {
+        assert!(at <= self.len);
+
+        let mut cut = &mut self.head;
+        for _ in 0..at {
+            cut = &mut cut.as_mut().unwrap().next;
+        }
+
+        let other = List {
+            head: cut.take(),
+            len: self.len - at,
+        };
+        self.len = at;
+        other
+    }
+}
+
+impl<T> List<T> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp)
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    // Bottom-up merge sort: runs of `width` nodes are cut off and merged
+    // pairwise, doubling `width` until a single run is left.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+        while width < self.len {
+            let mut rest = self.head.take();
+            let mut tail = &mut self.head;
+
+            while rest.is_some() {
+                let mut left = rest;
+                let mut right = cut(&mut left, width);
+                rest = cut(&mut right, width);
+
+                *tail = merge(left, right, &mut compare);
+                while let Some(node) = tail {
+                    tail = &mut node.next;
+                }
+            }
+
+            width *= 2;
+        }
+    }
+
+    pub fn merge_sorted(&mut self, mut other: List<T>)
+    where
+        T: Ord,
+    {
+        let left = self.head.take();
+        let right = other.head.take();
+        self.head = merge(left, right, &mut T::cmp);
+        self.len += std::mem::take(&mut other.len);
+    }
+}
+
+fn cut<T>(mut link: &mut Link<T>, n: usize) -> Link<T> {
+    for _ in 0..n {
+        match link {
+            Some(node) => link = &mut node.next,
+            None => return None,
+        }
+    }
+    link.take()
+}
+
+fn merge<T, F>(mut left: Link<T>, mut right: Link<T>, compare: &mut F) -> Link<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut head = None;
+    let mut tail = &mut head;
+
+    while let (Some(l), Some(r)) = (&left, &right) {
+        let from = if compare(&r.elem, &l.elem) == Ordering::Less {
+            &mut right
+        } else {
+            &mut left
+        };
+        let mut node = from.take().unwrap();
+        *from = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+
+    *tail = if left.is_some() { left } else { right };
+    head
+}
+
+impl<T> List<T>
+where
+    T: PartialEq,
+{
+    pub fn contains(&self, elem: &T) -> bool {
+        self.iter().any(|x| x == elem)
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for List<T> {
+    fn clone(&self) -> Self {
+        let mut new_list = Self::new();
+        let mut tail = &mut new_list.head;
+        for elem in self.iter() {
+            let node = tail.insert(Box::new(Node {
+                elem: elem.clone(),
+                next: None,
+            }));
+            tail = &mut node.next;
+        }
+        new_list.len = self.len;
+        new_list
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: Hash> Hash for List<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for elem in self.iter() {
+            elem.hash(state);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
 }
 
 impl<T> Drop for List<T> {
@@ -53,8 +251,11 @@ impl<T> Drop for List<T> {
 
 pub struct IntoIter<T>(List<T>);
 
-impl<T> List<T> {
-    pub fn into_iter(self) -> IntoIter<T> {
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
 }
@@ -65,50 +266,238 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
+    len: usize,
 }
 
 impl<T> List<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
+            len: self.len,
         }
     }
 }
 
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
             self.next = node.next.as_deref();
+            self.len -= 1;
             &node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
 pub struct IterMut<'a, T> {
     next: Option<&'a mut Node<T>>,
+    len: usize,
 }
 
 impl<T> List<T> {
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut(),
+            len: self.len,
         }
     }
 }
 
+impl<'a, T> IntoIterator for &'a mut List<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next.take().map(|node| {
             self.next = node.next.as_deref_mut();
+            self.len -= 1;
             &mut node.elem
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub struct Drain<'a, T> {
+    list: &'a mut List<T>,
+}
+
+impl<T> List<T> {
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { list: self }
+    }
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.list.clear();
+    }
+}
+
+pub struct CursorMut<'a, T> {
+    // `head` is set while the cursor sits before the first element
+    head: Option<&'a mut Link<T>>,
+    cur: Option<&'a mut Node<T>>,
+    index: usize,
+    len: &'a mut usize,
+}
+
+impl<T> List<T> {
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            head: Some(&mut self.head),
+            cur: None,
+            index: 0,
+            len: &mut self.len,
+        }
+    }
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.cur.as_ref().map(|_| self.index - 1)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.cur.as_mut().map(|node| &mut node.elem)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_link().as_mut().map(|node| &mut node.elem)
+    }
+
+    pub fn move_next(&mut self) -> bool {
+        if self.next_link().is_none() {
+            return false;
+        }
+
+        self.cur = match self.head.take() {
+            Some(head) => head.as_deref_mut(),
+            None => self.cur.take().and_then(|node| node.next.as_deref_mut()),
+        };
+        self.index += 1;
+        true
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let link = self.next_link();
+        let next = link.take();
+        *link = Some(Box::new(Node { elem, next }));
+        *self.len += 1;
+    }
+
+    pub fn remove_after(&mut self) -> Option<T> {
+        let link = self.next_link();
+        let mut node = link.take()?;
+        *link = node.next.take();
+        *self.len -= 1;
+        Some(node.elem)
+    }
+
+    pub fn split_after(&mut self) -> List<T> {
+        let head = self.next_link().take();
+        let len = *self.len - self.index;
+        *self.len = self.index;
+        List { head, len }
+    }
+
+    // O(1) in the size of `self`, O(n) in the size of `other`.
+    pub fn splice_after(&mut self, mut other: List<T>) {
+        let mut spliced = other.head.take();
+        let len = std::mem::take(&mut other.len);
+
+        let mut tail = &mut spliced;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+
+        let link = self.next_link();
+        *tail = link.take();
+        *link = spliced;
+        *self.len += len;
+    }
+
+    fn next_link(&mut self) -> &mut Link<T> {
+        match self.head {
+            Some(ref mut head) => head,
+            None => &mut self.cur.as_mut().unwrap().next,
+        }
+    }
+}
+
+// Elements are pushed in iteration order, so the last one ends up on top.
+impl<T> Extend<T> for List<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
 }
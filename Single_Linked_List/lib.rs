@@ -1,4 +1,14 @@
+pub mod arena_list;
+pub mod atomic_stack;
+pub mod fifth;
 pub mod first;
+pub mod fourth;
+pub mod pvec;
+pub mod queue;
+pub mod skip_list;
+pub mod sync;
+pub mod third;
+pub mod unrolled;
 
 #[cfg(test)]
 mod test {
@@ -98,4 +108,1435 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn len_and_clear() {
+        let mut list = List::new();
+        assert!(list.is_empty());
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.len(), 2);
+        list.pop();
+        assert_eq!(list.len(), 1);
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list = List::new();
+        list.reverse();
+        for i in 0..5 {
+            list.push(i);
+        }
+        list.reverse();
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn append_and_split_off() {
+        let mut a = List::new();
+        let mut b = List::new();
+        a.push(2);
+        a.push(1);
+        b.push(4);
+        b.push(3);
+
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(a.len(), 4);
+        assert_eq!(format!("{:?}", a), "[1, 2, 3, 4]");
+
+        let c = a.split_off(1);
+        assert_eq!(format!("{:?}", a), "[1]");
+        assert_eq!(format!("{:?}", c), "[2, 3, 4]");
+        assert_eq!(c.len(), 3);
+
+        let mut d = a.split_off(0);
+        assert!(a.is_empty());
+        d.append(&mut a);
+        assert_eq!(d.len(), 1);
+        assert!(d.split_off(1).is_empty());
+    }
+
+    #[test]
+    fn traits() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut a = List::new();
+        a.push(1);
+        a.push(2);
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert!(a.contains(&1));
+        assert!(!a.contains(&3));
+
+        let hash = |list: &List<i32>| {
+            let mut hasher = DefaultHasher::new();
+            list.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+
+        a.push(3);
+        assert_ne!(a, b);
+        assert_eq!(b.len(), 2);
+        assert_eq!(format!("{:?}", b), "[2, 1]");
+        assert_eq!(List::<i32>::default(), List::new());
+    }
+
+    #[test]
+    fn iterator_traits() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert_eq!(list.len(), 3);
+        list.extend(vec![4, 5]);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        iter.next();
+        assert_eq!(iter.size_hint(), (4, Some(4)));
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        let mut seen = vec![];
+        for elem in &list {
+            seen.push(*elem);
+        }
+        assert_eq!(seen, vec![50, 40, 30, 20, 10]);
+
+        let evens: List<i32> = list.into_iter().filter(|x| x % 20 == 0).collect();
+        let mut into_iter = evens.into_iter();
+        assert_eq!(into_iter.len(), 2);
+        assert_eq!(into_iter.next(), Some(20));
+        assert_eq!(into_iter.next(), Some(40));
+        assert_eq!(into_iter.next(), None);
+        assert_eq!(into_iter.next(), None);
+    }
+
+    #[test]
+    fn drain() {
+        let mut list: List<i32> = (0..5).collect();
+        let mut drain = list.drain();
+        assert_eq!(drain.len(), 5);
+        assert_eq!(drain.next(), Some(4));
+        assert_eq!(drain.next(), Some(3));
+        drop(drain);
+        assert!(list.is_empty());
+
+        list.push(7);
+        assert_eq!(list.drain().sum::<i32>(), 7);
+        assert_eq!(list.peek(), None);
+    }
+
+    #[test]
+    fn cursor_edit() {
+        let mut list: List<i32> = (0..10).rev().collect();
+        let mut cursor = list.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+
+        // drop every odd element in place
+        while let Some(next) = cursor.peek_next() {
+            if *next % 2 == 1 {
+                cursor.remove_after();
+            } else {
+                cursor.move_next();
+            }
+        }
+        assert_eq!(cursor.current(), Some(&mut 8));
+        assert_eq!(cursor.index(), Some(4));
+        assert!(!cursor.move_next());
+        assert_eq!(format!("{:?}", list), "[0, 2, 4, 6, 8]");
+        assert_eq!(list.len(), 5);
+
+        let mut cursor = list.cursor_mut();
+        cursor.insert_after(-1);
+        cursor.move_next();
+        cursor.move_next();
+        cursor.insert_after(1);
+        assert_eq!(cursor.remove_after(), Some(1));
+        cursor.insert_after(1);
+        assert_eq!(format!("{:?}", list), "[-1, 0, 1, 2, 4, 6, 8]");
+        assert_eq!(list.len(), 7);
+    }
+
+    #[test]
+    fn cursor_split_splice() {
+        let mut list: List<i32> = (0..6).rev().collect();
+        let mut cursor = list.cursor_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let mut tail = cursor.split_after();
+        assert_eq!(cursor.remove_after(), None);
+        assert_eq!(format!("{:?}", list), "[0, 1]");
+        assert_eq!(format!("{:?}", tail), "[2, 3, 4, 5]");
+        assert_eq!((list.len(), tail.len()), (2, 4));
+
+        let mut cursor = tail.cursor_mut();
+        cursor.move_next();
+        cursor.splice_after((7..=8).rev().collect());
+        cursor.splice_after(List::new());
+        assert_eq!(format!("{:?}", tail), "[2, 7, 8, 3, 4, 5]");
+        assert_eq!(tail.len(), 6);
+
+        let mut cursor = list.cursor_mut();
+        cursor.splice_after(tail);
+        let rest = cursor.split_after();
+        assert!(list.is_empty());
+        assert_eq!(rest.len(), 8);
+        assert_eq!(format!("{:?}", rest), "[2, 7, 8, 3, 4, 5, 0, 1]");
+    }
+
+    #[test]
+    fn sort() {
+        let mut list: List<i32> = List::new();
+        list.sort();
+        assert!(list.is_empty());
+
+        let mut seed = 7u32;
+        let mut values = vec![];
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            values.push((seed >> 16) % 50);
+        }
+        let mut list: List<u32> = values.iter().copied().collect();
+        list.sort();
+        values.sort();
+        assert_eq!(list.len(), 1000);
+        assert!(list.iter().eq(values.iter()));
+
+        list.sort_by(|a, b| b.cmp(a));
+        assert!(list.iter().eq(values.iter().rev()));
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let mut list: List<(i32, char)> = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (0, 'e')]
+            .into_iter()
+            .rev()
+            .collect();
+        list.sort_by_key(|pair| pair.0);
+        assert_eq!(
+            format!("{:?}", list),
+            "[(0, 'e'), (1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]"
+        );
+    }
+
+    #[test]
+    fn merge_sorted() {
+        let mut a: List<i32> = [1, 3, 5, 7].into_iter().rev().collect();
+        let b: List<i32> = [2, 3, 4, 10, 11].into_iter().rev().collect();
+        a.merge_sorted(b);
+        assert_eq!(a.len(), 9);
+        assert_eq!(format!("{:?}", a), "[1, 2, 3, 3, 4, 5, 7, 10, 11]");
+
+        a.merge_sorted(List::new());
+        assert_eq!(a.len(), 9);
+        let mut empty = List::new();
+        empty.merge_sorted(a);
+        assert_eq!(empty.len(), 9);
+    }
+}
+
+#[cfg(test)]
+mod test_fifth {
+    use super::fifth::List;
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+
+        list.push_back(4);
+        list.push_back(5);
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_front(), Some(4));
+        assert_eq!(list.pop_front(), Some(5));
+        assert_eq!(list.pop_front(), None);
+
+        // the tail must be reset once the queue drains
+        list.push_back(6);
+        list.push_back(7);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop_front(), Some(6));
+        assert_eq!(list.pop_front(), Some(7));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_back_mut(), None);
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        assert_eq!(list.peek(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&3));
+        if let Some(front) = list.peek_mut() {
+            *front = 10;
+        }
+        if let Some(back) = list.peek_back_mut() {
+            *back = 30;
+        }
+        list.push_back(4);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.peek_back(), Some(&4));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list: List<i32> = (1..=3).collect();
+        assert!(list.iter().copied().eq(1..=3));
+        assert_eq!(list.iter().len(), 3);
+
+        for elem in &mut list {
+            *elem *= 10;
+        }
+        list.push_back(40);
+        assert_eq!(list.iter_mut().next(), Some(&mut 10));
+        assert_eq!(list.pop_front(), Some(10));
+        list.push_back(50);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(20));
+        assert_eq!(iter.next(), Some(30));
+        assert_eq!(iter.next(), Some(40));
+        assert_eq!(iter.next(), Some(50));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn miri_food() {
+        let mut list = List::new();
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        assert!(list.pop_front() == Some(1));
+        list.push_back(4);
+        assert!(list.pop_front() == Some(2));
+        list.push_back(5);
+
+        assert!(list.peek() == Some(&3));
+        list.push_back(6);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert!(list.peek() == Some(&30));
+        assert!(list.pop_front() == Some(30));
+
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&400));
+        assert_eq!(iter.next(), Some(&500));
+        assert_eq!(iter.next(), Some(&600));
+        assert_eq!(iter.next(), None);
+
+        assert!(list.pop_front() == Some(400));
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert!(list.peek() == Some(&5000));
+        list.push_back(7);
+
+        // drop the rest while it is still non-empty
+    }
+}
+
+#[cfg(test)]
+mod test_fourth {
+    use super::fourth::List;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Tracked(i32, Rc<Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let mut list = List::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(2);
+        list.push_front(1);
+        list.push_back(3);
+        list.push_back(4);
+
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        assert!(list.is_empty());
+
+        list.push_back(5);
+        assert_eq!(list.pop_front(), Some(5));
+        list.push_front(6);
+        assert_eq!(list.pop_back(), Some(6));
+    }
+
+    #[test]
+    fn peek() {
+        let mut list = List::new();
+        assert!(list.peek_front().is_none());
+        assert!(list.peek_back_mut().is_none());
+
+        list.push_front(1);
+        list.push_front(2);
+        list.push_front(3);
+        assert_eq!(*list.peek_front().unwrap(), 3);
+        assert_eq!(*list.peek_back().unwrap(), 1);
+
+        *list.peek_front_mut().unwrap() = 30;
+        *list.peek_back_mut().unwrap() = 10;
+        assert_eq!(list.pop_front(), Some(30));
+        assert_eq!(list.pop_back(), Some(10));
+        assert_eq!(*list.peek_front().unwrap(), 2);
+        assert_eq!(*list.peek_back().unwrap(), 2);
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = List::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn no_leaks() {
+        let dropped = Rc::new(Cell::new(0));
+        let mut list = List::new();
+        for i in 0..10 {
+            list.push_back(Tracked(i, dropped.clone()));
+            list.push_front(Tracked(-i, dropped.clone()));
+        }
+        assert_eq!(list.pop_back().map(|t| t.0), Some(9));
+        assert_eq!(list.pop_front().map(|t| t.0), Some(-9));
+        assert_eq!(dropped.get(), 2);
+
+        drop(list);
+        assert_eq!(dropped.get(), 20);
+        assert_eq!(Rc::strong_count(&dropped), 1);
+
+        let dropped = Rc::new(Cell::new(0));
+        let mut list = List::new();
+        for i in 0..5 {
+            list.push_back(Tracked(i, dropped.clone()));
+        }
+        let mut iter = list.into_iter();
+        iter.next_back();
+        drop(iter);
+        assert_eq!(dropped.get(), 5);
+        assert_eq!(Rc::strong_count(&dropped), 1);
+    }
+
+    #[test]
+    fn long_list() {
+        let mut list = List::new();
+        for i in 0..100000 {
+            list.push_back(i);
+        }
+        drop(list);
+    }
+}
+
+#[cfg(test)]
+mod test_atomic_stack {
+    use super::atomic_stack::AtomicStack;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    struct Tracked(Arc<AtomicUsize>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let stack = AtomicStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn concurrent_push_pop() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 10_000;
+
+        let stack = Arc::new(AtomicStack::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                thread::spawn(move || {
+                    let mut popped = vec![];
+                    for i in 0..PER_THREAD {
+                        stack.push(t * PER_THREAD + i);
+                        if i % 2 == 1 {
+                            popped.extend(stack.pop());
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut seen: Vec<usize> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        while let Some(x) = stack.pop() {
+            seen.push(x);
+        }
+        seen.sort();
+        assert_eq!(seen, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn no_double_drop_or_leak() {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 5_000;
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let stack = Arc::new(AtomicStack::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let stack = Arc::clone(&stack);
+                let dropped = Arc::clone(&dropped);
+                thread::spawn(move || {
+                    for _ in 0..PER_THREAD {
+                        stack.push(Tracked(dropped.clone()));
+                        if t % 2 == 0 {
+                            drop(stack.pop());
+                        }
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        let remaining = THREADS * PER_THREAD - dropped.load(Ordering::Relaxed);
+        assert_eq!(remaining, THREADS / 2 * PER_THREAD);
+        drop(stack);
+        assert_eq!(dropped.load(Ordering::Relaxed), THREADS * PER_THREAD);
+        assert_eq!(Arc::strong_count(&dropped), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_arena_list {
+    use super::arena_list::ArenaList;
+    use super::first::List;
+    use std::time::Instant;
+
+    #[test]
+    fn basics() {
+        let mut list = ArenaList::new();
+        assert_eq!(list.pop(), None);
+        assert!(list.is_empty());
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek(), Some(&3));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(2));
+
+        list.push(4);
+        list.push(5);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn peek_mut() {
+        let mut list = ArenaList::new();
+        assert_eq!(list.peek_mut(), None);
+        list.push(1);
+        list.push(2);
+        if let Some(value) = list.peek_mut() {
+            *value = 42;
+        }
+        assert_eq!(list.peek(), Some(&42));
+        assert_eq!(list.pop(), Some(42));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list: ArenaList<i32> = (1..=4).collect();
+        assert_eq!(format!("{:?}", list), "[4, 3, 2, 1]");
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(&4));
+        assert_eq!(iter.len(), 3);
+
+        for value in list.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [40, 30, 20, 10]);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [40, 30, 20, 10]);
+    }
+
+    #[test]
+    fn free_slots_are_reused() {
+        let mut list = ArenaList::new();
+        list.extend(0..8);
+        let capacity = list.capacity();
+
+        for _ in 0..5 {
+            list.pop();
+        }
+        list.extend(10..15);
+        assert_eq!(list.len(), 8);
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [14, 13, 12, 11, 10, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn compact() {
+        let mut list = ArenaList::new();
+        list.extend(0..100);
+        for _ in 0..90 {
+            list.pop();
+        }
+        list.push(-1);
+        for value in list.iter_mut() {
+            *value += 1;
+        }
+        let before = list.iter().copied().collect::<Vec<_>>();
+
+        list.compact();
+        assert_eq!(list.len(), 11);
+        assert!(list.capacity() < 100);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), before);
+
+        list.push(7);
+        assert_eq!(list.pop(), Some(7));
+        assert_eq!(list.pop(), Some(0));
+
+        let mut empty: ArenaList<i32> = ArenaList::new();
+        empty.push(1);
+        empty.pop();
+        empty.compact();
+        assert!(empty.is_empty());
+        assert_eq!(empty.peek(), None);
+    }
+
+    // rustc --test lib.rs -O && ./lib --ignored --nocapture bench
+    #[test]
+    #[ignore]
+    fn bench_against_boxed_list() {
+        const N: usize = 1_000_000;
+        const ROUNDS: usize = 5;
+
+        let start = Instant::now();
+        let mut sum = 0;
+        for _ in 0..ROUNDS {
+            let mut list = List::new();
+            for i in 0..N {
+                list.push(i);
+            }
+            sum += list.iter().sum::<usize>();
+            while let Some(value) = list.pop() {
+                sum -= value;
+            }
+        }
+        let boxed = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            let mut list = ArenaList::new();
+            for i in 0..N {
+                list.push(i);
+            }
+            sum += list.iter().sum::<usize>();
+            while let Some(value) = list.pop() {
+                sum -= value;
+            }
+        }
+        let arena = start.elapsed();
+
+        assert_eq!(sum, 0);
+        println!(
+            "push/iter/pop of {} elements x{}: first::List {:?}, ArenaList {:?}",
+            N, ROUNDS, boxed, arena
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_unrolled {
+    use super::unrolled::UnrolledList;
+    use std::cell::Cell;
+
+    fn contents<const B: usize>(list: &UnrolledList<i32, B>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut list: UnrolledList<i32, 4> = UnrolledList::new();
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.peek(), None);
+
+        for i in 0..10 {
+            list.push(i);
+        }
+        assert_eq!(list.len(), 10);
+        assert_eq!(list.peek(), Some(&9));
+        if let Some(value) = list.peek_mut() {
+            *value = 90;
+        }
+        assert_eq!(list.pop(), Some(90));
+        for i in (0..9).rev() {
+            assert_eq!(list.pop(), Some(i));
+        }
+        assert_eq!(list.pop(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn insert_and_remove_match_vec() {
+        let mut list: UnrolledList<i32, 4> = UnrolledList::new();
+        let mut model = Vec::new();
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            seed >> 8
+        };
+
+        for round in 0..2000 {
+            if model.is_empty() || next() % 3 != 0 {
+                let at = next() as usize % (model.len() + 1);
+                list.insert(at, round);
+                model.insert(at, round);
+            } else {
+                let at = next() as usize % model.len();
+                assert_eq!(list.remove(at), model.remove(at));
+            }
+            assert_eq!(list.len(), model.len());
+        }
+        assert_eq!(contents(&list), model);
+        for (i, value) in model.iter().enumerate() {
+            assert_eq!(list.get(i), Some(value));
+        }
+        assert_eq!(list.get(model.len()), None);
+
+        while !model.is_empty() {
+            let at = next() as usize % model.len();
+            assert_eq!(list.remove(at), model.remove(at));
+        }
+        assert!(list.is_empty());
+        assert_eq!(list.iter().next(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut list: UnrolledList<i32, 4> = UnrolledList::new();
+        list.push(1);
+        list.insert(2, 2);
+    }
+
+    #[test]
+    fn split_off_and_append() {
+        let mut list: UnrolledList<i32, 3> = (0..10).rev().collect();
+        let mut tail = list.split_off(4);
+        assert_eq!(contents(&list), [0, 1, 2, 3]);
+        assert_eq!(contents(&tail), [4, 5, 6, 7, 8, 9]);
+
+        tail.insert(1, 40);
+        list.append(&mut tail);
+        assert!(tail.is_empty());
+        assert_eq!(contents(&list), [0, 1, 2, 3, 4, 40, 5, 6, 7, 8, 9]);
+
+        let empty = list.split_off(list.len());
+        assert!(empty.is_empty());
+        let all = list.split_off(0);
+        assert!(list.is_empty());
+        assert_eq!(all.len(), 11);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list: UnrolledList<i32, 2> = (1..=5).collect();
+        assert_eq!(format!("{:?}", list), "[5, 4, 3, 2, 1]");
+        assert_eq!(list.iter().len(), 5);
+
+        for value in &mut list {
+            *value *= 2;
+        }
+        if let Some(value) = list.get_mut(2) {
+            *value = 0;
+        }
+        assert_eq!(contents(&list), [10, 8, 0, 4, 2]);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [10, 8, 0, 4, 2]);
+    }
+
+    #[test]
+    fn drops_every_element() {
+        struct Tracked<'a>(&'a Cell<usize>);
+
+        impl Drop for Tracked<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut list: UnrolledList<Tracked, 8> = UnrolledList::new();
+        for _ in 0..100 {
+            list.push(Tracked(&drops));
+        }
+        drop(list.remove(50));
+        let tail = list.split_off(30);
+        assert_eq!(drops.get(), 1);
+        drop(tail);
+        assert_eq!(drops.get(), 70);
+        list.clear();
+        assert_eq!(drops.get(), 100);
+    }
+}
+
+#[cfg(test)]
+mod test_skip_list {
+    use super::skip_list::{Iter, SkipListMap};
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    #[test]
+    fn basics() {
+        let mut map = SkipListMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.first(), None);
+        assert_eq!(map.last(), None);
+
+        assert_eq!(map.insert(3, "c"), None);
+        assert_eq!(map.insert(1, "a"), None);
+        assert_eq!(map.insert(2, "b"), None);
+        assert_eq!(map.insert(2, "B"), Some("b"));
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.get(&2), Some(&"B"));
+        assert_eq!(map.get(&4), None);
+        if let Some(value) = map.get_mut(&1) {
+            *value = "A";
+        }
+        assert_eq!(map.first(), Some((&1, &"A")));
+        assert_eq!(map.last(), Some((&3, &"c")));
+        assert_eq!(format!("{:?}", map), r#"{1: "A", 2: "B", 3: "c"}"#);
+
+        assert_eq!(map.remove(&2), Some("B"));
+        assert_eq!(map.remove(&2), None);
+        assert!(!map.contains_key(&2));
+        assert_eq!(map.remove(&1), Some("A"));
+        assert_eq!(map.remove(&3), Some("c"));
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn range_and_rank() {
+        let map: SkipListMap<i32, i32> = (0..50).map(|i| (i * 2, i)).collect();
+
+        let keys = |it: Iter<'_, i32, i32>| it.map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(map.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(keys(map.range(..4)), [0, 2]);
+        assert_eq!(keys(map.range(95..)), [96, 98]);
+        assert_eq!(keys(map.range(200..)), []);
+        assert_eq!(keys(map.range(11..12)), []);
+        assert_eq!(
+            keys(map.range((Bound::Included(20), Bound::Excluded(10)))),
+            []
+        );
+        assert_eq!(map.range(..).count(), 50);
+
+        for i in 0..50 {
+            assert_eq!(map.rank(&(i * 2)), Some(i as usize));
+            assert_eq!(map.rank(&(i * 2 + 1)), None);
+        }
+    }
+
+    #[test]
+    fn matches_btree_map() {
+        let mut map = SkipListMap::with_seed(42);
+        let mut model = BTreeMap::new();
+        let mut seed = 1u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for _ in 0..5000 {
+            let key = next() % 300;
+            if next() % 3 == 0 {
+                assert_eq!(map.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(map.insert(key, key * 10), model.insert(key, key * 10));
+            }
+            assert_eq!(map.len(), model.len());
+        }
+
+        assert!(map.iter().eq(model.iter()));
+        assert_eq!(map.first(), model.first_key_value());
+        assert_eq!(map.last(), model.last_key_value());
+        assert!(map.range(50..150).eq(model.range(50..150)));
+        for (i, key) in model.keys().enumerate() {
+            assert_eq!(map.rank(key), Some(i));
+        }
+    }
+
+    #[test]
+    fn seeded_levels_are_reproducible() {
+        let build = |seed| {
+            let mut map = SkipListMap::with_seed(seed);
+            for i in 0..100u32 {
+                map.insert(i, ());
+            }
+            format!("{:?}", (0..100).map(|i| map.rank(&i)).collect::<Vec<_>>())
+        };
+        assert_eq!(build(7), build(7));
+
+        let mut a = SkipListMap::with_seed(0);
+        let mut b = SkipListMap::with_seed(0);
+        for i in 0..1000 {
+            a.insert(i, ());
+            b.insert(999 - i, ());
+        }
+        assert!(a.iter().eq(b.iter()));
+        a.clear();
+        assert!(a.is_empty());
+        a.insert(1, ());
+        assert_eq!(a.rank(&1), Some(0));
+    }
+}
+
+#[cfg(test)]
+mod test_third {
+    use super::third::List;
+    use std::ptr;
+
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.head(), None);
+
+        let list = list.prepend(1).prepend(2).prepend(3);
+        assert_eq!(list.head(), Some(&3));
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&2));
+
+        let list = list.tail();
+        assert_eq!(list.head(), Some(&1));
+
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+
+        let list = list.tail();
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn queries() {
+        let list: List<i32> = (1..=5).collect();
+        assert_eq!(format!("{:?}", list), "[1, 2, 3, 4, 5]");
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.nth(0), Some(&1));
+        assert_eq!(list.nth(4), Some(&5));
+        assert_eq!(list.nth(5), None);
+        assert_eq!(list.last(), Some(&5));
+        assert_eq!(list.fold(0, |acc, x| acc + x), 15);
+
+        let empty: List<i32> = List::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.last(), None);
+        assert_eq!(empty, List::new());
+        assert_ne!(list, empty);
+    }
+
+    #[test]
+    fn combinators() {
+        let list: List<i32> = (1..=5).collect();
+
+        assert_eq!(list.reverse(), (1..=5).rev().collect());
+        assert_eq!(
+            list.map(|x| x * 10),
+            [10, 20, 30, 40, 50].into_iter().collect()
+        );
+        assert_eq!(list.filter(|x| x % 2 == 1), [1, 3, 5].into_iter().collect());
+        assert_eq!(list.take(2), [1, 2].into_iter().collect());
+        assert_eq!(list.take(9), list);
+        assert_eq!(list.drop_n(3), [4, 5].into_iter().collect());
+        assert!(list.drop_n(9).is_empty());
+
+        let letters: List<char> = "abc".chars().collect();
+        assert_eq!(
+            list.zip(&letters),
+            [(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect()
+        );
+
+        let nested: List<List<i32>> = vec![list.take(2), List::new(), list.drop_n(3)]
+            .into_iter()
+            .collect();
+        assert_eq!(nested.concat(), [1, 2, 4, 5].into_iter().collect());
+        assert!(List::<List<i32>>::new().concat().is_empty());
+
+        // the original is untouched by all of the above
+        assert_eq!(list, (1..=5).collect());
+    }
+
+    #[test]
+    fn structure_is_shared() {
+        let list: List<i32> = (1..=5).collect();
+
+        let copy = list.clone();
+        assert!(ptr::eq(copy.head().unwrap(), list.head().unwrap()));
+
+        let suffix = list.drop_n(2);
+        assert!(ptr::eq(suffix.head().unwrap(), list.nth(2).unwrap()));
+
+        let prefix: List<i32> = [-1, 0].into_iter().collect();
+        let joined = prefix.append(&list);
+        assert!(ptr::eq(joined.nth(2).unwrap(), list.head().unwrap()));
+        assert!(!ptr::eq(joined.head().unwrap(), prefix.head().unwrap()));
+
+        let filtered = list.filter(|&x| x != 2);
+        assert_eq!(filtered, [1, 3, 4, 5].into_iter().collect());
+        assert!(!ptr::eq(filtered.head().unwrap(), list.head().unwrap()));
+        assert!(ptr::eq(filtered.nth(1).unwrap(), list.nth(2).unwrap()));
+
+        let unchanged = list.filter(|_| true);
+        assert!(ptr::eq(unchanged.head().unwrap(), list.head().unwrap()));
+
+        let nested: List<List<i32>> = vec![prefix.clone(), list.clone()].into_iter().collect();
+        let flat = nested.concat();
+        assert!(ptr::eq(flat.nth(2).unwrap(), list.head().unwrap()));
+    }
+
+    #[test]
+    fn long_list_drops() {
+        let list: List<u32> = (0..200_000).collect();
+        let shared = list.drop_n(100_000);
+        drop(list);
+        assert_eq!(shared.head(), Some(&100_000));
+    }
+}
+
+#[cfg(test)]
+mod test_sync {
+    use super::sync::List;
+    use std::thread;
+
+    #[test]
+    fn same_api_as_third() {
+        let list: List<i32> = (1..=5).collect();
+        assert_eq!(list.prepend(0).head(), Some(&0));
+        assert_eq!(list.tail().head(), Some(&2));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.last(), Some(&5));
+        assert_eq!(list.reverse(), (1..=5).rev().collect());
+        assert_eq!(list.filter(|x| x % 2 == 0), [2, 4].into_iter().collect());
+        assert_eq!(
+            list.drop_n(3).append(&list.take(1)),
+            [4, 5, 1].into_iter().collect()
+        );
+        assert_eq!(format!("{:?}", list.map(|x| x * x)), "[1, 4, 9, 16, 25]");
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<List<String>>();
+
+        let base: List<u64> = (0..10_000).collect();
+        let sum = base.fold(0, |acc, x| acc + x);
+
+        thread::scope(|s| {
+            let handles = (0..8u64)
+                .map(|t| {
+                    let base = base.clone();
+                    s.spawn(move || {
+                        let mine = base.drop_n(t as usize).prepend(t * 1000);
+                        assert_eq!(mine.head(), Some(&(t * 1000)));
+                        assert_eq!(mine.tail().head(), Some(&t));
+                        base.fold(0, |acc, x| acc + x)
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), sum);
+            }
+        });
+
+        assert_eq!(base.len(), 10_000);
+    }
+
+    #[test]
+    fn concurrent_drops_of_a_long_list() {
+        let base: List<u32> = (0..200_000).collect();
+        let handles = (0..8)
+            .map(|t| {
+                let list = base.drop_n(t * 1000).prepend(0);
+                thread::spawn(move || drop(list))
+            })
+            .collect::<Vec<_>>();
+        drop(base);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_queue {
+    use super::queue::Queue;
+
+    fn contents(queue: &Queue<i32>) -> Vec<i32> {
+        queue.iter().copied().collect()
+    }
+
+    #[test]
+    fn fifo_order() {
+        let empty = Queue::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.head(), None);
+        assert!(empty.tail().is_empty());
+
+        let mut queue = empty.clone();
+        for i in 0..100 {
+            queue = queue.snoc(i);
+            assert_eq!(queue.head(), Some(&0));
+        }
+        assert_eq!(queue.len(), 100);
+
+        for i in 0..100 {
+            assert_eq!(queue.head(), Some(&i));
+            queue = queue.tail();
+            assert_eq!(queue.len(), 99 - i as usize);
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn old_versions_are_preserved() {
+        let q0: Queue<i32> = Queue::new();
+        let q1 = q0.snoc(1);
+        let q2 = q1.snoc(2);
+        let q3 = q2.snoc(3);
+        let q4 = q3.tail();
+        let q5 = q4.snoc(4);
+
+        // branch off an old version twice
+        let a = q2.snoc(20);
+        let b = q2.tail().snoc(30);
+
+        assert_eq!(contents(&q0), []);
+        assert_eq!(contents(&q1), [1]);
+        assert_eq!(contents(&q2), [1, 2]);
+        assert_eq!(contents(&q3), [1, 2, 3]);
+        assert_eq!(contents(&q4), [2, 3]);
+        assert_eq!(contents(&q5), [2, 3, 4]);
+        assert_eq!(contents(&a), [1, 2, 20]);
+        assert_eq!(contents(&b), [2, 30]);
+        assert_eq!(format!("{:?}", q5), "[2, 3, 4]");
+    }
+
+    #[test]
+    fn reusing_a_version_before_rotation_stays_cheap() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static CLONES: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Counted(u32);
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.with(|clones| clones.set(clones.get() + 1));
+                Counted(self.0)
+            }
+        }
+
+        // rotations fire on the snoc that makes the length 2^k - 1, so a
+        // queue of 1022 has equally long front and rear and rotates next
+        let base = (0..1022).fold(Queue::new(), |queue, i| queue.snoc(Counted(i)));
+        assert_eq!(base.len(), 1022);
+
+        CLONES.with(|clones| clones.set(0));
+        for i in 0..100 {
+            let next = base.snoc(Counted(5000 + i));
+            assert_eq!(next.head(), Some(&Counted(0)));
+            let next = next.tail().snoc(Counted(6000 + i));
+            assert_eq!(next.head(), Some(&Counted(1)));
+            assert_eq!(next.len(), 1023);
+        }
+        let clones = CLONES.with(Cell::get);
+        assert!(clones < 100 * 16, "{} clones for 100 reuses", clones);
+
+        let last = base.snoc(Counted(7000));
+        assert_eq!(last.iter().count(), 1023);
+        assert_eq!(last.iter().last(), Some(&Counted(7000)));
+    }
+
+    #[test]
+    fn long_queues_drop() {
+        let mut queue = (0..100_000).collect::<Queue<u32>>();
+        for i in 0..100_000 {
+            queue = queue.snoc(i);
+        }
+        for _ in 0..150_000 {
+            queue = queue.tail();
+        }
+        assert_eq!(queue.head(), Some(&50_000));
+    }
+
+    #[test]
+    fn matches_vec_deque_across_versions() {
+        use std::collections::VecDeque;
+
+        let mut versions = vec![(Queue::new(), VecDeque::new())];
+        let mut seed = 3u32;
+        for step in 0..3000 {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let (queue, model) = &versions[(seed >> 8) as usize % versions.len()];
+            let (queue, mut model) = (queue.clone(), model.clone());
+
+            let queue = if seed.is_multiple_of(3) {
+                model.pop_front();
+                queue.tail()
+            } else {
+                model.push_back(step);
+                queue.snoc(step)
+            };
+            assert_eq!(queue.len(), model.len());
+            assert_eq!(queue.head(), model.front());
+            versions.push((queue, model));
+        }
+
+        for (queue, model) in &versions {
+            assert!(queue.iter().eq(model.iter()));
+        }
+
+        let collected: Queue<i32> = (0..5).collect();
+        assert_eq!(contents(&collected.tail().snoc(5)), [1, 2, 3, 4, 5]);
+    }
+}
+
+#[cfg(test)]
+mod test_pvec {
+    use super::pvec::PVec;
+
+    #[test]
+    fn push_get_pop() {
+        let empty: PVec<usize> = PVec::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.get(0), None);
+        assert_eq!(empty.last(), None);
+        assert!(empty.pop_back().is_empty());
+
+        // crosses the tail, one trie level and a root split
+        let mut vec = empty.clone();
+        for i in 0..40_000 {
+            vec = vec.push_back(i);
+        }
+        assert_eq!(vec.len(), 40_000);
+        assert_eq!(vec.first(), Some(&0));
+        assert_eq!(vec.last(), Some(&39_999));
+        assert_eq!(vec.get(40_000), None);
+        assert!((0..40_000).step_by(7).all(|i| vec[i] == i));
+        assert!(vec.iter().copied().eq(0..40_000));
+        assert_eq!(vec.iter().len(), 40_000);
+
+        for i in (0..40_000).rev() {
+            assert_eq!(vec.last(), Some(&i));
+            vec = vec.pop_back();
+        }
+        assert!(vec.is_empty());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn versions_are_independent() {
+        let base: PVec<i32> = (0..2000).collect();
+        let changed = base.set(5, -5).set(1500, -1500).set(1999, -1999);
+        let grown = base.push_back(2000);
+        let shrunk = base.pop_back().pop_back();
+
+        assert_eq!(base[5], 5);
+        assert_eq!(base[1500], 1500);
+        assert_eq!(base[1999], 1999);
+        assert_eq!(base.len(), 2000);
+
+        assert_eq!(changed[5], -5);
+        assert_eq!(changed[1500], -1500);
+        assert_eq!(changed[1999], -1999);
+        assert_eq!(changed[6], 6);
+
+        assert_eq!(grown.len(), 2001);
+        assert_eq!(grown.last(), Some(&2000));
+        assert_eq!(shrunk.len(), 1998);
+        assert_eq!(shrunk.last(), Some(&1997));
+        assert!(base.iter().copied().eq(0..2000));
+    }
+
+    #[test]
+    fn matches_vec_across_versions() {
+        let mut versions = vec![(PVec::new(), Vec::new())];
+        let mut seed = 11u64;
+        for step in 0..4000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let (pvec, model) = &versions[seed as usize % versions.len()];
+            let (pvec, mut model): (PVec<u64>, Vec<u64>) = (pvec.clone(), model.clone());
+
+            let pvec = match seed % 5 {
+                0 => {
+                    model.pop();
+                    pvec.pop_back()
+                }
+                1 if !model.is_empty() => {
+                    let index = (seed >> 20) as usize % model.len();
+                    model[index] = step;
+                    pvec.set(index, step)
+                }
+                2 if !model.is_empty() => {
+                    let start = (seed >> 20) as usize % model.len();
+                    let end = start + (seed >> 40) as usize % (model.len() - start + 1);
+                    model = model[start..end].to_vec();
+                    pvec.slice(start..end)
+                }
+                _ => {
+                    model.extend(step..step + (seed >> 40) % 70);
+                    pvec.concat(&(step..step + (seed >> 40) % 70).collect())
+                }
+            };
+            assert_eq!(pvec.len(), model.len());
+            versions.push((pvec, model));
+        }
+
+        for (pvec, model) in &versions {
+            assert!(pvec.iter().eq(model.iter()));
+        }
+    }
+
+    #[test]
+    fn slice_and_concat() {
+        let vec: PVec<i32> = (0..100).collect();
+        assert!(vec.slice(10..20).iter().copied().eq(10..20));
+        assert!(vec.slice(..=40).iter().copied().eq(0..=40));
+        assert!(vec.slice(90..).iter().copied().eq(90..100));
+        assert!(vec.slice(50..50).is_empty());
+        assert_eq!(vec.slice(..), vec);
+
+        let joined = vec.slice(..50).concat(&vec.slice(50..));
+        assert_eq!(joined, vec);
+        assert_eq!(format!("{:?}", vec.slice(3..6)), "[3, 4, 5]");
+    }
+
+    #[test]
+    fn slices_share_structure() {
+        let vec: PVec<usize> = (0..40_000).collect();
+        for end in [0, 1, 31, 32, 33, 500, 1024, 1025, 1056, 33_000, 39_999] {
+            let prefix = vec.slice(..end);
+            assert!(prefix.iter().copied().eq(0..end));
+            if end > 32 {
+                assert!(std::ptr::eq(&prefix[0], &vec[0]));
+                assert!(std::ptr::eq(&prefix[end - 32], &vec[end - 32]));
+            }
+            assert!(prefix.push_back(end).iter().copied().eq(0..=end));
+        }
+
+        let prefix = vec.slice(..500);
+        assert!(std::ptr::eq(&prefix[479], &vec[479]));
+        assert!(!std::ptr::eq(&prefix[480], &vec[480]));
+
+        let middle = vec.slice(32..100);
+        assert!(middle.iter().copied().eq(32..100));
+        assert!(std::ptr::eq(&middle[0], &vec[32]));
+        assert!(std::ptr::eq(&middle[31], &vec[63]));
+
+        let a: PVec<usize> = (0..64).collect();
+        let b: PVec<usize> = (64..200).collect();
+        let joined = a.concat(&b);
+        assert!(joined.iter().copied().eq(0..200));
+        assert!(std::ptr::eq(&joined[0], &a[0]));
+        assert!(std::ptr::eq(&joined[64], &b[0]));
+        assert!(std::ptr::eq(&joined[191], &b[127]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_out_of_bounds() {
+        let vec: PVec<i32> = (0..3).collect();
+        vec.set(3, 0);
+    }
 }
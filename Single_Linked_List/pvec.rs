@@ -0,0 +1,456 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Bound, Index, RangeBounds};
+use std::rc::Rc;
+use std::slice;
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+#[derive(Clone)]
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    fn branch(&self) -> &Vec<Rc<Node<T>>> {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("expected a branch"),
+        }
+    }
+
+    fn branch_mut(&mut self) -> &mut Vec<Rc<Node<T>>> {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("expected a branch"),
+        }
+    }
+
+    fn leaf(&self) -> &Vec<T> {
+        match self {
+            Node::Leaf(elems) => elems,
+            Node::Branch(_) => unreachable!("expected a leaf"),
+        }
+    }
+
+    fn leaf_mut(&mut self) -> &mut Vec<T> {
+        match self {
+            Node::Leaf(elems) => elems,
+            Node::Branch(_) => unreachable!("expected a leaf"),
+        }
+    }
+}
+
+// A bit-partitioned trie in the style of Clojure's vector: full 32-element
+// leaves hang off `root`, packed to the left, and the last 1..=32 elements
+// live in `tail` so that pushes and pops rarely touch the trie at all.
+// Updates go through `Rc::make_mut`, which copies a node only while it is
+// shared with another version.
+pub struct PVec<T> {
+    root: Rc<Node<T>>,
+    tail: Rc<Node<T>>,
+    shift: u32,
+    len: usize,
+}
+
+impl<T> PVec<T> {
+    pub fn new() -> Self {
+        PVec {
+            root: Rc::new(Node::Branch(Vec::new())),
+            tail: Rc::new(Node::Leaf(Vec::new())),
+            shift: BITS,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.leaf_for(index).get(index & MASK)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vec: self,
+            index: 0,
+            chunk: [].iter(),
+        }
+    }
+
+    fn tail_offset(&self) -> usize {
+        if self.len == 0 {
+            0
+        } else {
+            (self.len - 1) & !MASK
+        }
+    }
+
+    fn leaf_for(&self, index: usize) -> &[T] {
+        self.leaf_node(index).leaf()
+    }
+
+    fn leaf_node(&self, index: usize) -> &Rc<Node<T>> {
+        if index >= self.tail_offset() {
+            return &self.tail;
+        }
+
+        let mut node = &self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            node = &node.branch()[(index >> level) & MASK];
+            level -= BITS;
+        }
+        node
+    }
+}
+
+impl<T: Clone> PVec<T> {
+    pub fn push_back(&self, elem: T) -> Self {
+        let mut vec = self.clone();
+        vec.push_mut(elem);
+        vec
+    }
+
+    pub fn set(&self, index: usize, elem: T) -> Self {
+        assert!(
+            index < self.len,
+            "index out of bounds: the len is {} but the index is {}",
+            self.len,
+            index
+        );
+
+        let mut vec = self.clone();
+        if index >= vec.tail_offset() {
+            Rc::make_mut(&mut vec.tail).leaf_mut()[index & MASK] = elem;
+            return vec;
+        }
+
+        let mut node = &mut vec.root;
+        let mut level = vec.shift;
+        while level > 0 {
+            node = &mut Rc::make_mut(node).branch_mut()[(index >> level) & MASK];
+            level -= BITS;
+        }
+        Rc::make_mut(node).leaf_mut()[index & MASK] = elem;
+        vec
+    }
+
+    pub fn pop_back(&self) -> Self {
+        let mut vec = self.clone();
+        vec.pop_mut();
+        vec
+    }
+
+    // Keeps the first `n` elements, sharing every leaf but the new tail.
+    pub fn take(&self, n: usize) -> Self {
+        if n >= self.len {
+            return self.clone();
+        }
+        if n == 0 {
+            return Self::new();
+        }
+
+        let mut vec = self.clone();
+        vec.len = n;
+        let tail_offset = vec.tail_offset();
+        if tail_offset < self.tail_offset() {
+            vec.tail = self.leaf_node(n - 1).clone();
+            vec.root = match Self::trim(&self.root, self.shift, tail_offset) {
+                Some(root) => root,
+                None => Rc::new(Node::Branch(Vec::new())),
+            };
+            vec.shift = self.shift;
+            while vec.shift > BITS && vec.root.branch().len() == 1 {
+                vec.root = vec.root.branch()[0].clone();
+                vec.shift -= BITS;
+            }
+            if vec.root.branch().is_empty() {
+                vec.shift = BITS;
+            }
+        }
+        if vec.tail.leaf().len() > n - tail_offset {
+            Rc::make_mut(&mut vec.tail)
+                .leaf_mut()
+                .truncate(n - tail_offset);
+        }
+        vec
+    }
+
+    // Slices share leaves with `self` wherever `range.start` is a multiple
+    // of 32; other starts copy the elements.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end && end <= self.len,
+            "range {}..{} out of bounds for length {}",
+            start,
+            end,
+            self.len
+        );
+
+        let prefix = self.take(end);
+        if start == 0 {
+            return prefix;
+        }
+        let mut vec = Self::new();
+        vec.append_from(&prefix, start);
+        vec
+    }
+
+    // Shares all of `self`. The leaves of `other` are shared too when
+    // `self.len()` is a multiple of 32; otherwise they are copied.
+    pub fn concat(&self, other: &PVec<T>) -> Self {
+        let mut vec = self.clone();
+        vec.append_from(other, 0);
+        vec
+    }
+
+    fn append_from(&mut self, other: &PVec<T>, mut from: usize) {
+        while from < other.len {
+            if self.len & MASK == 0 && from & MASK == 0 && from + WIDTH <= other.len {
+                self.push_full_leaf(other.leaf_node(from).clone());
+                from += WIDTH;
+            } else {
+                self.push_mut(other[from].clone());
+                from += 1;
+            }
+        }
+    }
+
+    // Keeps the first `count` elements of the subtree, a multiple of 32.
+    fn trim(node: &Rc<Node<T>>, level: u32, count: usize) -> Option<Rc<Node<T>>> {
+        if count == 0 {
+            return None;
+        }
+
+        let children = node.branch();
+        let keep = ((count - 1) >> level) + 1;
+        let mut trimmed = children[..keep - 1].to_vec();
+        if level == BITS {
+            trimmed.push(children[keep - 1].clone());
+        } else {
+            let rest = count - ((keep - 1) << level);
+            trimmed.extend(Self::trim(&children[keep - 1], level - BITS, rest));
+        }
+        Some(Rc::new(Node::Branch(trimmed)))
+    }
+
+    fn push_mut(&mut self, elem: T) {
+        if self.len - self.tail_offset() < WIDTH {
+            Rc::make_mut(&mut self.tail).leaf_mut().push(elem);
+            self.len += 1;
+            return;
+        }
+
+        self.push_tail(Rc::new(Node::Leaf(vec![elem])));
+        self.len += 1;
+    }
+
+    // Appends a shared leaf of exactly 32 elements; `len` must be a multiple
+    // of 32, so the current tail, if any, is full.
+    fn push_full_leaf(&mut self, leaf: Rc<Node<T>>) {
+        if self.len == 0 {
+            self.tail = leaf;
+        } else {
+            self.push_tail(leaf);
+        }
+        self.len += WIDTH;
+    }
+
+    // Moves the full tail into the trie and installs `new_tail`.
+    fn push_tail(&mut self, new_tail: Rc<Node<T>>) {
+        let leaf = std::mem::replace(&mut self.tail, new_tail);
+        let full = self.len;
+        if (full >> BITS) > (1 << self.shift) {
+            let old_root = std::mem::replace(&mut self.root, Rc::new(Node::Branch(Vec::new())));
+            let path = Self::new_path(self.shift, leaf);
+            self.root = Rc::new(Node::Branch(vec![old_root, path]));
+            self.shift += BITS;
+        } else {
+            Self::push_leaf(&mut self.root, self.shift, full, leaf);
+        }
+    }
+
+    // `full` is the number of elements in the trie once `leaf` is added.
+    fn push_leaf(node: &mut Rc<Node<T>>, level: u32, full: usize, leaf: Rc<Node<T>>) {
+        let children = Rc::make_mut(node).branch_mut();
+        let index = ((full - 1) >> level) & MASK;
+        if level == BITS {
+            children.push(leaf);
+        } else if index < children.len() {
+            Self::push_leaf(&mut children[index], level - BITS, full, leaf);
+        } else {
+            children.push(Self::new_path(level - BITS, leaf));
+        }
+    }
+
+    fn new_path(level: u32, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+        if level == 0 {
+            leaf
+        } else {
+            Rc::new(Node::Branch(vec![Self::new_path(level - BITS, leaf)]))
+        }
+    }
+
+    fn pop_mut(&mut self) {
+        if self.len <= 1 {
+            *self = Self::new();
+            return;
+        }
+
+        if self.len - self.tail_offset() > 1 {
+            Rc::make_mut(&mut self.tail).leaf_mut().pop();
+        } else {
+            self.tail = Self::pop_leaf(&mut self.root, self.shift);
+            if self.shift > BITS && self.root.branch().len() == 1 {
+                self.root = self.root.branch()[0].clone();
+                self.shift -= BITS;
+            }
+        }
+        self.len -= 1;
+    }
+
+    // Detaches the rightmost leaf, dropping any branch it leaves empty.
+    fn pop_leaf(node: &mut Rc<Node<T>>, level: u32) -> Rc<Node<T>> {
+        let children = Rc::make_mut(node).branch_mut();
+        if level == BITS {
+            return children.pop().unwrap();
+        }
+
+        let last = children.last_mut().unwrap();
+        let leaf = Self::pop_leaf(last, level - BITS);
+        if last.branch().is_empty() {
+            children.pop();
+        }
+        leaf
+    }
+}
+
+impl<T> Default for PVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PVec<T> {
+    fn clone(&self) -> Self {
+        PVec {
+            root: self.root.clone(),
+            tail: self.tail.clone(),
+            shift: self.shift,
+            len: self.len,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for PVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for PVec<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<usize> for PVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(elem) => elem,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len, index
+            ),
+        }
+    }
+}
+
+impl<T: Clone> Extend<T> for PVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_mut(elem);
+        }
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+pub struct Iter<'a, T> {
+    vec: &'a PVec<T>,
+    index: usize,
+    chunk: slice::Iter<'a, T>,
+}
+
+impl<'a, T> IntoIterator for &'a PVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.vec.len {
+            return None;
+        }
+        if self.index & MASK == 0 {
+            self.chunk = self.vec.leaf_for(self.index).iter();
+        }
+        self.index += 1;
+        self.chunk.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
@@ -0,0 +1,233 @@
+use std::cell::{Cell, OnceCell};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::third::List;
+
+// Okasaki's real-time queue. Elements leave from the lazy stream `front` and
+// arrive on the strict `rear`. When `rear` outgrows `front` the two are
+// combined by a suspended `front ++ reverse(rear)` rotation that produces one
+// element per force, and `schedule` points at the first unforced cell of it.
+// Each operation forces exactly one cell, so every version (old ones
+// included) costs O(1) per operation, worst case.
+pub struct Queue<T> {
+    front: Stream<T>,
+    rear: List<T>,
+    schedule: Stream<T>,
+    len: usize,
+}
+
+enum StreamCell<T> {
+    Nil,
+    Cons(T, Stream<T>),
+}
+
+// A memoised stream node: `cell` is filled from `rotation` the first time the
+// node is forced, and every version that shares the node sees the result.
+struct Susp<T> {
+    cell: OnceCell<StreamCell<T>>,
+    rotation: Cell<Option<Rotation<T>>>,
+}
+
+struct Stream<T>(Rc<Susp<T>>);
+
+struct Rotation<T> {
+    front: Stream<T>,
+    rear: List<T>,
+    acc: Stream<T>,
+}
+
+impl<T> Stream<T> {
+    fn ready(cell: StreamCell<T>) -> Self {
+        Stream(Rc::new(Susp {
+            cell: OnceCell::from(cell),
+            rotation: Cell::new(None),
+        }))
+    }
+
+    fn nil() -> Self {
+        Self::ready(StreamCell::Nil)
+    }
+}
+
+impl<T: Clone> Stream<T> {
+    // `rear` must be exactly one longer than `front`.
+    fn rotate(front: Stream<T>, rear: List<T>, acc: Stream<T>) -> Self {
+        Stream(Rc::new(Susp {
+            cell: OnceCell::new(),
+            rotation: Cell::new(Some(Rotation { front, rear, acc })),
+        }))
+    }
+
+    fn force(&self) -> &StreamCell<T> {
+        self.0.cell.get_or_init(|| match self.0.rotation.take() {
+            Some(rotation) => rotation.step(),
+            None => unreachable!("stream node has neither a value nor a rotation"),
+        })
+    }
+}
+
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        Stream(self.0.clone())
+    }
+}
+
+impl<T: Clone> Rotation<T> {
+    fn step(self) -> StreamCell<T> {
+        let last = self.rear.head().expect("rotation ran out of rear").clone();
+        match self.front.force() {
+            StreamCell::Nil => StreamCell::Cons(last, self.acc),
+            StreamCell::Cons(elem, rest) => StreamCell::Cons(
+                elem.clone(),
+                Stream::rotate(
+                    rest.clone(),
+                    self.rear.tail(),
+                    Stream::ready(StreamCell::Cons(last, self.acc)),
+                ),
+            ),
+        }
+    }
+}
+
+impl<T> Susp<T> {
+    fn take_next(&mut self) -> Option<Rc<Susp<T>>> {
+        match self.cell.take() {
+            Some(StreamCell::Cons(_, next)) => Some(next.0),
+            _ => None,
+        }
+    }
+}
+
+// Long forced streams would otherwise be freed recursively.
+impl<T> Drop for Susp<T> {
+    fn drop(&mut self) {
+        let mut next = self.take_next();
+        while let Some(node) = next {
+            next = match Rc::try_unwrap(node) {
+                Ok(mut node) => node.take_next(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue {
+            front: Stream::nil(),
+            rear: List::new(),
+            schedule: Stream::nil(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: Clone> Queue<T> {
+    pub fn head(&self) -> Option<&T> {
+        match self.front.force() {
+            StreamCell::Cons(elem, _) => Some(elem),
+            StreamCell::Nil => None,
+        }
+    }
+
+    pub fn snoc(&self, elem: T) -> Self {
+        Self::exec(
+            self.front.clone(),
+            self.rear.prepend(elem),
+            &self.schedule,
+            self.len + 1,
+        )
+    }
+
+    pub fn tail(&self) -> Self {
+        match self.front.force() {
+            StreamCell::Cons(_, rest) => Self::exec(
+                rest.clone(),
+                self.rear.clone(),
+                &self.schedule,
+                self.len - 1,
+            ),
+            StreamCell::Nil => self.clone(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut front = vec![];
+        let mut cur = self.front.force();
+        while let StreamCell::Cons(elem, rest) = cur {
+            front.push(elem);
+            cur = rest.force();
+        }
+        let rear = self.rear.iter().collect::<Vec<_>>();
+        front.into_iter().chain(rear.into_iter().rev())
+    }
+
+    fn exec(front: Stream<T>, rear: List<T>, schedule: &Stream<T>, len: usize) -> Self {
+        match schedule.force() {
+            StreamCell::Cons(_, rest) => Queue {
+                front,
+                rear,
+                schedule: rest.clone(),
+                len,
+            },
+            StreamCell::Nil => {
+                let front = Stream::rotate(front, rear, Stream::nil());
+                Queue {
+                    schedule: front.clone(),
+                    front,
+                    rear: List::new(),
+                    len,
+                }
+            }
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Queue {
+            front: self.front.clone(),
+            rear: self.rear.clone(),
+            schedule: self.schedule.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Clone + fmt::Debug> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    // Builds an already-forced front, so the schedule is simply all of it.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems = iter.into_iter().collect::<Vec<_>>();
+        let len = elems.len();
+        let front = elems.into_iter().rev().fold(Stream::nil(), |rest, elem| {
+            Stream::ready(StreamCell::Cons(elem, rest))
+        });
+        Queue {
+            schedule: front.clone(),
+            front,
+            rear: List::new(),
+            len,
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+
+const MAX_LEVEL: usize = 32;
+const NIL: usize = usize::MAX;
+const DEFAULT_SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+// `span` counts how many level-0 steps the link skips, which is what lets
+// `rank` add its way down the tower instead of walking the bottom list.
+#[derive(Clone, Copy)]
+struct Link {
+    next: usize,
+    span: usize,
+}
+
+const EMPTY: Link = Link { next: NIL, span: 0 };
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    links: Vec<Link>,
+}
+
+// Nodes live in a slab and link to each other by index, which keeps every
+// tower a plain singly linked list without any unsafe pointer juggling.
+pub struct SkipListMap<K, V> {
+    head: [Link; MAX_LEVEL],
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    level: usize,
+    len: usize,
+    rng: u64,
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        SkipListMap {
+            head: [EMPTY; MAX_LEVEL],
+            nodes: Vec::new(),
+            free: Vec::new(),
+            level: 0,
+            len: 0,
+            rng: seed,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.head = [EMPTY; MAX_LEVEL];
+        self.nodes.clear();
+        self.free.clear();
+        self.level = 0;
+        self.len = 0;
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|index| &self.node(index).value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.find(key)?;
+        Some(&mut self.node_mut(index).value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (update, rank) = self.predecessors(&key);
+
+        let found = self.links(update[0])[0].next;
+        if found != NIL && self.node(found).key == key {
+            return Some(std::mem::replace(&mut self.node_mut(found).value, value));
+        }
+
+        let level = self.random_level();
+        if level > self.level {
+            for i in self.level..level {
+                self.head[i].span = self.len;
+            }
+            self.level = level;
+        }
+
+        let mut links = vec![EMPTY; level];
+        for (i, link) in links.iter_mut().enumerate() {
+            let prev = self.links(update[i])[i];
+            link.next = prev.next;
+            link.span = prev.span - (rank[0] - rank[i]);
+        }
+        let index = self.alloc(Node { key, value, links });
+
+        for i in 0..self.level {
+            let prev = &mut self.links_mut(update[i])[i];
+            if i < level {
+                prev.next = index;
+                prev.span = rank[0] - rank[i] + 1;
+            } else {
+                prev.span += 1;
+            }
+        }
+
+        self.len += 1;
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (update, _) = self.predecessors(key);
+
+        let found = self.links(update[0])[0].next;
+        if found == NIL || self.node(found).key != *key {
+            return None;
+        }
+
+        let node = self.nodes[found].take().unwrap();
+        self.free.push(found);
+
+        for (i, &at) in update.iter().enumerate().take(self.level) {
+            let prev = &mut self.links_mut(at)[i];
+            if prev.next == found {
+                prev.next = node.links[i].next;
+                prev.span += node.links[i].span;
+            }
+            prev.span -= 1;
+        }
+
+        while self.level > 0 && self.head[self.level - 1].next == NIL {
+            self.level -= 1;
+            self.head[self.level] = EMPTY;
+        }
+
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entry(self.head[0].next)
+    }
+
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut at = None;
+        for i in (0..self.level).rev() {
+            while self.links(at)[i].next != NIL {
+                at = Some(self.links(at)[i].next);
+            }
+        }
+        at.and_then(|index| self.entry(index))
+    }
+
+    // Zero-based position of `key` in sorted order.
+    pub fn rank(&self, key: &K) -> Option<usize> {
+        let mut at = None;
+        let mut rank = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let link = self.links(at)[i];
+                if link.next == NIL || self.node(link.next).key > *key {
+                    break;
+                }
+                rank += link.span;
+                at = Some(link.next);
+            }
+            if let Some(index) = at {
+                if self.node(index).key == *key {
+                    return Some(rank - 1);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            map: self,
+            next: self.head[0].next,
+            end: NIL,
+        }
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.seek(|key| key < start),
+            Bound::Excluded(start) => self.seek(|key| key <= start),
+            Bound::Unbounded => self.head[0].next,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.seek(|key| key <= end),
+            Bound::Excluded(end) => self.seek(|key| key < end),
+            Bound::Unbounded => NIL,
+        };
+
+        // an inverted range starts at or past its own end
+        let empty = match (self.entry(next), self.entry(end)) {
+            (None, _) => true,
+            (Some((start, _)), Some((end, _))) => start >= end,
+            (Some(_), None) => false,
+        };
+        Iter {
+            map: self,
+            next: if empty { NIL } else { next },
+            end,
+        }
+    }
+
+    // First node whose key does not satisfy `before`, which must hold for a
+    // prefix of the keys.
+    fn seek<F>(&self, mut before: F) -> usize
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let mut at = None;
+        for i in (0..self.level).rev() {
+            loop {
+                let next = self.links(at)[i].next;
+                if next == NIL || !before(&self.node(next).key) {
+                    break;
+                }
+                at = Some(next);
+            }
+        }
+        self.links(at)[0].next
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        let index = self.seek(|k| k < key);
+        if index != NIL && self.node(index).key == *key {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    // Last node before `key` on every level, and its one-based position.
+    fn predecessors(&self, key: &K) -> ([Option<usize>; MAX_LEVEL], [usize; MAX_LEVEL]) {
+        let mut update = [None; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut at = None;
+        let mut pos = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let link = self.links(at)[i];
+                if link.next == NIL || self.node(link.next).key >= *key {
+                    break;
+                }
+                pos += link.span;
+                at = Some(link.next);
+            }
+            update[i] = at;
+            rank[i] = pos;
+        }
+        (update, rank)
+    }
+
+    fn random_level(&mut self) -> usize {
+        // splitmix64, so every seed (zero included) gives a usable stream
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+impl<K, V> SkipListMap<K, V> {
+    fn node(&self, index: usize) -> &Node<K, V> {
+        self.nodes[index].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<K, V> {
+        self.nodes[index].as_mut().unwrap()
+    }
+
+    fn links(&self, at: Option<usize>) -> &[Link] {
+        match at {
+            Some(index) => &self.node(index).links,
+            None => &self.head,
+        }
+    }
+
+    fn links_mut(&mut self, at: Option<usize>) -> &mut [Link] {
+        match at {
+            Some(index) => &mut self.node_mut(index).links,
+            None => &mut self.head,
+        }
+    }
+
+    fn entry(&self, index: usize) -> Option<(&K, &V)> {
+        self.nodes
+            .get(index)
+            .and_then(Option::as_ref)
+            .map(|node| (&node.key, &node.value))
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipListMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipListMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    map: &'a SkipListMap<K, V>,
+    next: usize,
+    end: usize,
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SkipListMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            self.next = NIL;
+        }
+        let entry = self.map.entry(self.next)?;
+        self.next = self.map.node(self.next).links[0].next;
+        Some(entry)
+    }
+}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
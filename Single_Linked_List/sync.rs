@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use crate::third::{sealed, SharedList, SharedPtr};
+
+// `third::SharedList` behind `Arc`, so a list and its suffixes can be shared
+// across threads whenever `T: Send + Sync`.
+pub type List<T> = SharedList<T, ArcPtr>;
+
+pub enum ArcPtr {}
+
+impl sealed::Sealed for ArcPtr {}
+
+impl SharedPtr for ArcPtr {
+    type Ptr<T> = Arc<T>;
+
+    fn new<T>(value: T) -> Arc<T> {
+        Arc::new(value)
+    }
+
+    fn get_mut<T>(ptr: &mut Arc<T>) -> Option<&mut T> {
+        Arc::get_mut(ptr)
+    }
+
+    // `Arc::into_inner` rather than `try_unwrap`: when two threads drop the
+    // last two handles to a node at once, both `try_unwrap`s can fail and the
+    // node would then be freed recursively by whoever drops last.
+    fn into_inner<T>(ptr: Arc<T>) -> Option<T> {
+        Arc::into_inner(ptr)
+    }
+}
@@ -1,32 +1,75 @@
+use std::fmt;
+use std::ops::Deref;
 use std::rc::Rc;
 
-pub struct List<T> {
-    head: Link<T>,
+pub(crate) mod sealed {
+    pub trait Sealed {}
 }
 
-type Link<T> = Option<Rc<Node<T>>>;
+// The reference-counted pointer the nodes live behind: `RcPtr` here, and
+// `sync::ArcPtr` for lists that cross threads. Sealed, because `Drop` counts
+// on `into_inner` handing back the node only to its last owner.
+pub trait SharedPtr: sealed::Sealed {
+    type Ptr<T>: Clone + Deref<Target = T>;
 
-struct Node<T> {
+    fn new<T>(value: T) -> Self::Ptr<T>;
+
+    fn get_mut<T>(ptr: &mut Self::Ptr<T>) -> Option<&mut T>;
+
+    fn into_inner<T>(ptr: Self::Ptr<T>) -> Option<T>;
+}
+
+pub enum RcPtr {}
+
+impl sealed::Sealed for RcPtr {}
+
+impl SharedPtr for RcPtr {
+    type Ptr<T> = Rc<T>;
+
+    fn new<T>(value: T) -> Rc<T> {
+        Rc::new(value)
+    }
+
+    fn get_mut<T>(ptr: &mut Rc<T>) -> Option<&mut T> {
+        Rc::get_mut(ptr)
+    }
+
+    fn into_inner<T>(ptr: Rc<T>) -> Option<T> {
+        Rc::try_unwrap(ptr).ok()
+    }
+}
+
+// The list is generic over its pointer kind. `List` here and `sync::List`
+// pin it to `Rc` and `Arc`, which also lets `List::new()` infer it.
+pub type List<T> = SharedList<T, RcPtr>;
+
+pub struct SharedList<T, P: SharedPtr> {
+    head: Link<T, P>,
+}
+
+type Link<T, P> = Option<<P as SharedPtr>::Ptr<Node<T, P>>>;
+
+struct Node<T, P: SharedPtr> {
     elem: T,
-    next: Link<T>,
+    next: Link<T, P>,
 }
 
-impl<T> List<T> {
-    pub fn new() -> List<T> {
-        List { head: None }
+impl<T, P: SharedPtr> SharedList<T, P> {
+    pub fn new() -> SharedList<T, P> {
+        SharedList { head: None }
     }
 
-    pub fn prepend(&self, elem: T) -> List<T> {
-        List {
-            head: Some(Rc::new(Node {
+    pub fn prepend(&self, elem: T) -> SharedList<T, P> {
+        SharedList {
+            head: Some(P::new(Node {
                 elem,
                 next: self.head.clone(),
             })),
         }
     }
 
-    pub fn tail(&self) -> List<T> {
-        List {
+    pub fn tail(&self) -> SharedList<T, P> {
+        SharedList {
             head: self.head.as_ref().and_then(|node| node.next.clone()),
         }
     }
@@ -34,13 +77,161 @@ impl<T> List<T> {
     pub fn head(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.elem)
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        self.iter().nth(n)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.iter().last()
+    }
+
+    pub fn fold<A, F>(&self, init: A, f: F) -> A
+    where
+        F: FnMut(A, &T) -> A,
+    {
+        self.iter().fold(init, f)
+    }
+
+    pub fn drop_n(&self, n: usize) -> SharedList<T, P> {
+        let mut link = &self.head;
+        for _ in 0..n {
+            match link {
+                Some(node) => link = &node.next,
+                None => break,
+            }
+        }
+        SharedList { head: link.clone() }
+    }
+
+    pub fn map<U, F>(&self, f: F) -> SharedList<U, P>
+    where
+        F: FnMut(&T) -> U,
+    {
+        SharedList::splice(self.iter().map(f), None)
+    }
+
+    // Builds the nodes for `elems` front to back and hangs `tail` off the
+    // last one. Each new node is still uniquely owned while we link it.
+    fn splice<I>(elems: I, tail: Link<T, P>) -> SharedList<T, P>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut head = None;
+        let mut cursor = &mut head;
+        for elem in elems {
+            let node = cursor.insert(P::new(Node { elem, next: None }));
+            cursor = &mut P::get_mut(node).unwrap().next;
+        }
+        *cursor = tail;
+        SharedList { head }
+    }
 }
 
-impl<T> Drop for List<T> {
+impl<T: Clone, P: SharedPtr> SharedList<T, P> {
+    pub fn reverse(&self) -> SharedList<T, P> {
+        self.fold(SharedList::new(), |acc, elem| acc.prepend(elem.clone()))
+    }
+
+    // Copies `self` and shares all of `other`.
+    pub fn append(&self, other: &SharedList<T, P>) -> SharedList<T, P> {
+        SharedList::splice(self.iter().cloned(), other.head.clone())
+    }
+
+    pub fn take(&self, n: usize) -> SharedList<T, P> {
+        SharedList::splice(self.iter().take(n).cloned(), None)
+    }
+
+    // Everything after the last rejected element is shared, not copied.
+    pub fn filter<F>(&self, mut f: F) -> SharedList<T, P>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let keep = self.iter().map(&mut f).collect::<Vec<_>>();
+        let last_rejected = match keep.iter().rposition(|&keep| !keep) {
+            Some(index) => index,
+            None => return self.clone(),
+        };
+
+        let prefix = self
+            .iter()
+            .zip(keep)
+            .take(last_rejected)
+            .filter(|&(_, keep)| keep)
+            .map(|(elem, _)| elem.clone());
+        let mut suffix = self.drop_n(last_rejected + 1);
+        SharedList::splice(prefix, suffix.head.take())
+    }
+
+    pub fn zip<U: Clone>(&self, other: &SharedList<U, P>) -> SharedList<(T, U), P> {
+        SharedList::splice(
+            self.iter()
+                .zip(other.iter())
+                .map(|(a, b)| (a.clone(), b.clone())),
+            None,
+        )
+    }
+}
+
+impl<T: Clone, P: SharedPtr> SharedList<SharedList<T, P>, P> {
+    // Every list but the last is copied; the last one is shared.
+    pub fn concat(&self) -> SharedList<T, P> {
+        let mut lists = self.iter().collect::<Vec<_>>();
+        let last = lists.pop().cloned().unwrap_or_default();
+        lists
+            .into_iter()
+            .rev()
+            .fold(last, |acc, list| list.append(&acc))
+    }
+}
+
+impl<T, P: SharedPtr> Default for SharedList<T, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, P: SharedPtr> Clone for SharedList<T, P> {
+    fn clone(&self) -> Self {
+        SharedList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T: PartialEq, P: SharedPtr> PartialEq for SharedList<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, P: SharedPtr> Eq for SharedList<T, P> {}
+
+impl<T: fmt::Debug, P: SharedPtr> fmt::Debug for SharedList<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, P: SharedPtr> FromIterator<T> for SharedList<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SharedList::splice(iter, None)
+    }
+}
+
+impl<T, P: SharedPtr> Drop for SharedList<T, P> {
     fn drop(&mut self) {
         let mut head = self.head.take();
         while let Some(node) = head {
-            if let Ok(mut node) = Rc::try_unwrap(node) {
+            if let Some(mut node) = P::into_inner(node) {
                 head = node.next.take();
             } else {
                 break;
@@ -49,19 +240,28 @@ impl<T> Drop for List<T> {
     }
 }
 
-pub struct Iter<'a, T> {
-    next: Option<&'a Node<T>>,
+pub struct Iter<'a, T, P: SharedPtr> {
+    next: Option<&'a Node<T, P>>,
 }
 
-impl<T> List<T> {
-    pub fn iter(&self) -> Iter<'_, T> {
+impl<T, P: SharedPtr> SharedList<T, P> {
+    pub fn iter(&self) -> Iter<'_, T, P> {
         Iter {
             next: self.head.as_deref(),
         }
     }
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<'a, T, P: SharedPtr> IntoIterator for &'a SharedList<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+
+    fn into_iter(self) -> Iter<'a, T, P> {
+        self.iter()
+    }
+}
+
+impl<'a, T, P: SharedPtr> Iterator for Iter<'a, T, P> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -0,0 +1,444 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+
+// A fixed-capacity inline buffer; the first `len` slots are initialised.
+struct Chunk<T, const B: usize> {
+    elems: [MaybeUninit<T>; B],
+    len: usize,
+}
+
+impl<T, const B: usize> Chunk<T, B> {
+    fn new() -> Self {
+        Chunk {
+            elems: [const { MaybeUninit::uninit() }; B],
+            len: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == B
+    }
+
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.elems.as_ptr() as *const T, self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.elems.as_mut_ptr() as *mut T, self.len) }
+    }
+
+    fn insert(&mut self, index: usize, elem: T) {
+        debug_assert!(index <= self.len && !self.is_full());
+        unsafe {
+            let p = self.elems.as_mut_ptr().add(index);
+            ptr::copy(p, p.add(1), self.len - index);
+            p.write(MaybeUninit::new(elem));
+        }
+        self.len += 1;
+    }
+
+    fn remove(&mut self, index: usize) -> T {
+        debug_assert!(index < self.len);
+        self.len -= 1;
+        unsafe {
+            let p = self.elems.as_mut_ptr().add(index);
+            let elem = p.read().assume_init();
+            ptr::copy(p.add(1), p, self.len - index);
+            elem
+        }
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        debug_assert!(at <= self.len);
+        let mut other = Chunk::new();
+        other.len = self.len - at;
+        self.len = at;
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.elems.as_ptr().add(at),
+                other.elems.as_mut_ptr(),
+                other.len,
+            );
+        }
+        other
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        debug_assert!(self.len + other.len <= B);
+        unsafe {
+            ptr::copy_nonoverlapping(
+                other.elems.as_ptr(),
+                self.elems.as_mut_ptr().add(self.len),
+                other.len,
+            );
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+}
+
+impl<T, const B: usize> Drop for Chunk<T, B> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+// Every node holds at least one element. `insert` and `remove` split and merge
+// nodes so that all but the last stay at least half full; `append` and
+// `split_off` relink whole nodes and may leave one short node at the seam.
+pub struct UnrolledList<T, const B: usize> {
+    head: Link<T, B>,
+    len: usize,
+}
+
+type Link<T, const B: usize> = Option<Box<Node<T, B>>>;
+
+struct Node<T, const B: usize> {
+    chunk: Chunk<T, B>,
+    next: Link<T, B>,
+}
+
+impl<T, const B: usize> Node<T, B> {
+    fn new(chunk: Chunk<T, B>, next: Link<T, B>) -> Box<Self> {
+        Box::new(Node { chunk, next })
+    }
+}
+
+impl<T, const B: usize> UnrolledList<T, B> {
+    pub fn new() -> Self {
+        assert!(B >= 2, "chunk capacity must be at least 2");
+        UnrolledList { head: None, len: 0 }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        self.insert(0, elem);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        let mut cur = self.head.as_deref();
+        while let Some(node) = cur {
+            if index < node.chunk.len {
+                return node.chunk.as_slice().get(index);
+            }
+            index -= node.chunk.len;
+            cur = node.next.as_deref();
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        let mut cur = self.head.as_deref_mut();
+        while let Some(node) = cur {
+            if index < node.chunk.len {
+                return node.chunk.as_mut_slice().get_mut(index);
+            }
+            index -= node.chunk.len;
+            cur = node.next.as_deref_mut();
+        }
+        None
+    }
+
+    pub fn insert(&mut self, mut index: usize, elem: T) {
+        assert!(
+            index <= self.len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            self.len
+        );
+
+        let mut link = &mut self.head;
+        while index > link.as_ref().map_or(0, |node| node.chunk.len) {
+            index -= link.as_ref().unwrap().chunk.len;
+            link = &mut link.as_mut().unwrap().next;
+        }
+        self.len += 1;
+
+        let node = match link {
+            Some(node) => node,
+            None => {
+                let mut chunk = Chunk::new();
+                chunk.insert(0, elem);
+                *link = Some(Node::new(chunk, None));
+                return;
+            }
+        };
+
+        if node.chunk.is_full() {
+            let upper = node.chunk.split_off(B / 2);
+            node.next = Some(Node::new(upper, node.next.take()));
+            if index > node.chunk.len {
+                index -= node.chunk.len;
+                node.next.as_mut().unwrap().chunk.insert(index, elem);
+                return;
+            }
+        }
+        node.chunk.insert(index, elem);
+    }
+
+    pub fn remove(&mut self, mut index: usize) -> T {
+        assert!(
+            index < self.len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            self.len
+        );
+
+        let mut link = &mut self.head;
+        while index >= link.as_ref().unwrap().chunk.len {
+            index -= link.as_ref().unwrap().chunk.len;
+            link = &mut link.as_mut().unwrap().next;
+        }
+        self.len -= 1;
+
+        let node = link.as_mut().unwrap();
+        let elem = node.chunk.remove(index);
+
+        if node.chunk.len < B / 2 {
+            if let Some(next) = node.next.as_mut() {
+                if node.chunk.len + next.chunk.len <= B {
+                    let mut next = node.next.take().unwrap();
+                    node.chunk.append(&mut next.chunk);
+                    node.next = next.next.take();
+                } else {
+                    let stolen = next.chunk.remove(0);
+                    let len = node.chunk.len;
+                    node.chunk.insert(len, stolen);
+                }
+            }
+        }
+
+        if node.chunk.len == 0 {
+            *link = node.next.take();
+        }
+        elem
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        let mut tail = &mut self.head;
+        while let Some(node) = tail {
+            tail = &mut node.next;
+        }
+
+        *tail = other.head.take();
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    pub fn split_off(&mut self, mut at: usize) -> Self {
+        assert!(at <= self.len);
+
+        let len = self.len - at;
+        self.len = at;
+
+        let mut link = &mut self.head;
+        while at > 0 {
+            let node = link.as_mut().unwrap();
+            if at < node.chunk.len {
+                let upper = node.chunk.split_off(at);
+                node.next = Some(Node::new(upper, node.next.take()));
+                at = node.chunk.len;
+            }
+            at -= node.chunk.len;
+            link = &mut link.as_mut().unwrap().next;
+        }
+
+        UnrolledList {
+            head: link.take(),
+            len,
+        }
+    }
+}
+
+impl<T, const B: usize> Default for UnrolledList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const B: usize> fmt::Debug for UnrolledList<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const B: usize> Drop for UnrolledList<T, B> {
+    fn drop(&mut self) {
+        let mut cur_link = self.head.take();
+
+        while let Some(mut boxed_node) = cur_link {
+            cur_link = boxed_node.next.take();
+        }
+    }
+}
+
+pub struct IntoIter<T, const B: usize>(UnrolledList<T, B>);
+
+impl<T, const B: usize> IntoIterator for UnrolledList<T, B> {
+    type Item = T;
+    type IntoIter = IntoIter<T, B>;
+
+    fn into_iter(self) -> IntoIter<T, B> {
+        IntoIter(self)
+    }
+}
+
+impl<T, const B: usize> Iterator for IntoIter<T, B> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for IntoIter<T, B> {}
+
+impl<T, const B: usize> FusedIterator for IntoIter<T, B> {}
+
+pub struct Iter<'a, T, const B: usize> {
+    next: Option<&'a Node<T, B>>,
+    elems: slice::Iter<'a, T>,
+    len: usize,
+}
+
+impl<T, const B: usize> UnrolledList<T, B> {
+    pub fn iter(&self) -> Iter<'_, T, B> {
+        Iter {
+            next: self.head.as_deref(),
+            elems: [].iter(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a UnrolledList<T, B> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Iter<'a, T, B> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const B: usize> Iterator for Iter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(elem) = self.elems.next() {
+                self.len -= 1;
+                return Some(elem);
+            }
+            let node = self.next?;
+            self.elems = node.chunk.as_slice().iter();
+            self.next = node.next.as_deref();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for Iter<'_, T, B> {}
+
+impl<T, const B: usize> FusedIterator for Iter<'_, T, B> {}
+
+pub struct IterMut<'a, T, const B: usize> {
+    next: Option<&'a mut Node<T, B>>,
+    elems: slice::IterMut<'a, T>,
+    len: usize,
+}
+
+impl<T, const B: usize> UnrolledList<T, B> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, B> {
+        IterMut {
+            next: self.head.as_deref_mut(),
+            elems: [].iter_mut(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a mut UnrolledList<T, B> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, B>;
+
+    fn into_iter(self) -> IterMut<'a, T, B> {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T, const B: usize> Iterator for IterMut<'a, T, B> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(elem) = self.elems.next() {
+                self.len -= 1;
+                return Some(elem);
+            }
+            let Node { chunk, next } = self.next.take()?;
+            self.elems = chunk.as_mut_slice().iter_mut();
+            self.next = next.as_deref_mut();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for IterMut<'_, T, B> {}
+
+impl<T, const B: usize> FusedIterator for IterMut<'_, T, B> {}
+
+impl<T, const B: usize> Extend<T> for UnrolledList<T, B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for UnrolledList<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
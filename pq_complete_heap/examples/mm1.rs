@@ -0,0 +1,23 @@
+use pq_complete_heap::sim::mm1;
+
+fn main() {
+    let (arrival_rate, service_rate) = (0.8, 1.0);
+    let rho: f64 = arrival_rate / service_rate;
+    let report = mm1(arrival_rate, service_rate, 1_000_000.0, 2023);
+
+    println!("completed       {}", report.completed);
+    println!(
+        "utilization     {:.4} (analytic {:.4})",
+        report.utilization, rho
+    );
+    println!(
+        "mean in system  {:.4} (analytic {:.4})",
+        report.mean_in_system,
+        rho / (1.0 - rho)
+    );
+    println!(
+        "mean sojourn    {:.4} (analytic {:.4})",
+        report.mean_sojourn,
+        1.0 / (service_rate - arrival_rate)
+    );
+}
@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use crate::pq_complete_heap::PqCompleteHeap;
+
+type Task = Box<dyn FnOnce() + Send>;
+
+struct Job {
+    key: i128,
+    seq: u64,
+    task: Task,
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Job {}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key
+            .cmp(&other.key)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+struct Queue {
+    jobs: PqCompleteHeap<Job>,
+    seq: u64,
+    shutdown: bool,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    ready: Condvar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shutdown {
+    Drain,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinError {
+    Cancelled,
+    Panicked,
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinError::Cancelled => write!(f, "job was cancelled before it ran"),
+            JoinError::Panicked => write!(f, "job panicked"),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+pub struct JoinHandle<R> {
+    result: Receiver<thread::Result<R>>,
+}
+
+impl<R> JoinHandle<R> {
+    pub fn join(self) -> Result<R, JoinError> {
+        match self.result.recv() {
+            Ok(Ok(r)) => Ok(r),
+            Ok(Err(_)) => Err(JoinError::Panicked),
+            Err(_) => Err(JoinError::Cancelled),
+        }
+    }
+}
+
+pub struct PriorityExecutor {
+    shared: Arc<Shared>,
+    workers: Vec<thread::JoinHandle<()>>,
+    aging: Option<u64>,
+}
+
+impl PriorityExecutor {
+    pub fn new(threads: usize) -> Self {
+        Self::with_aging(threads, None)
+    }
+
+    // With `aging = Some(n)` a queued job gains one priority level for every
+    // `n` jobs spawned after it, so low priorities cannot starve forever.
+    pub fn with_aging(threads: usize, aging: Option<u64>) -> Self {
+        assert!(threads > 0, "executor needs at least one worker");
+        assert!(aging != Some(0), "aging interval must be positive");
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                jobs: PqCompleteHeap::new(),
+                seq: 0,
+                shutdown: false,
+            }),
+            ready: Condvar::new(),
+        });
+        let workers = (0..threads)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || work(&shared))
+            })
+            .collect();
+        PriorityExecutor {
+            shared,
+            workers,
+            aging,
+        }
+    }
+
+    pub fn spawn<F, R>(&self, priority: i64, job: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let task: Task = Box::new(move || {
+            let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(job)));
+        });
+
+        let mut queue = self.shared.queue.lock().unwrap();
+        let seq = queue.seq;
+        queue.seq += 1;
+        let key = match self.aging {
+            None => priority as i128,
+            Some(n) => priority as i128 * n as i128 - seq as i128,
+        };
+        queue.jobs.push(Job { key, seq, task });
+        drop(queue);
+        self.shared.ready.notify_one();
+
+        JoinHandle { result: rx }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.shared.queue.lock().unwrap().jobs.len()
+    }
+
+    pub fn shutdown(mut self, mode: Shutdown) {
+        self.stop(mode);
+    }
+
+    fn stop(&mut self, mode: Shutdown) {
+        let mut cancelled = vec![];
+        {
+            let mut queue = self.shared.queue.lock().unwrap();
+            queue.shutdown = true;
+            if mode == Shutdown::Cancel {
+                while let Some(job) = queue.jobs.pop() {
+                    cancelled.push(job);
+                }
+            }
+        }
+        drop(cancelled);
+        self.shared.ready.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for PriorityExecutor {
+    fn drop(&mut self) {
+        self.stop(Shutdown::Drain);
+    }
+}
+
+fn work(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if let Some(job) = queue.jobs.pop() {
+                    break job;
+                }
+                if queue.shutdown {
+                    return;
+                }
+                queue = shared.ready.wait(queue).unwrap();
+            }
+        };
+        (job.task)();
+    }
+}
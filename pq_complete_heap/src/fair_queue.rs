@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::pq_complete_heap::PqCompleteHeap;
+
+#[derive(Debug)]
+struct Head<F> {
+    finish: f64,
+    seq: u64,
+    flow: F,
+}
+
+impl<F> PartialEq for Head<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F> Eq for Head<F> {}
+
+impl<F> PartialOrd for Head<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F> Ord for Head<F> {
+    // smallest virtual finish time first, then arrival order
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .finish
+            .total_cmp(&self.finish)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug)]
+struct Flow<T> {
+    weight: f64,
+    last_finish: f64,
+    queue: VecDeque<(f64, u64, T)>,
+}
+
+impl<T> Flow<T> {
+    fn new() -> Self {
+        Flow {
+            weight: 1.0,
+            last_finish: 0.0,
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FairQueue<F, T>
+where
+    F: Hash + Eq + Clone,
+{
+    flows: HashMap<F, Flow<T>>,
+    heads: PqCompleteHeap<Head<F>>,
+    virtual_time: f64,
+    seq: u64,
+    len: usize,
+}
+
+impl<F, T> FairQueue<F, T>
+where
+    F: Hash + Eq + Clone,
+{
+    pub fn new() -> Self {
+        FairQueue {
+            flows: HashMap::new(),
+            heads: PqCompleteHeap::new(),
+            virtual_time: 0.0,
+            seq: 0,
+            len: 0,
+        }
+    }
+
+    pub fn set_weight(&mut self, flow: F, weight: f64) {
+        assert!(weight > 0.0, "flow weight must be positive");
+        self.flows.entry(flow).or_insert_with(Flow::new).weight = weight;
+    }
+
+    pub fn weight(&self, flow: &F) -> f64 {
+        self.flows.get(flow).map_or(1.0, |f| f.weight)
+    }
+
+    pub fn enqueue(&mut self, flow: F, cost: f64, item: T) {
+        assert!(cost >= 0.0, "cost must not be negative");
+        let virtual_time = self.virtual_time;
+        let seq = self.seq;
+        self.seq += 1;
+        self.len += 1;
+
+        let state = self.flows.entry(flow.clone()).or_insert_with(Flow::new);
+        let finish = state.last_finish.max(virtual_time) + cost / state.weight;
+        state.last_finish = finish;
+        state.queue.push_back((finish, seq, item));
+        if state.queue.len() == 1 {
+            self.heads.push(Head { finish, seq, flow });
+        }
+    }
+
+    pub fn dequeue(&mut self) -> Option<(F, T)> {
+        let head = self.heads.pop()?;
+        let state = self.flows.get_mut(&head.flow).unwrap();
+        let (finish, _, item) = state.queue.pop_front().unwrap();
+        if let Some(&(finish, seq, _)) = state.queue.front() {
+            self.heads.push(Head {
+                finish,
+                seq,
+                flow: head.flow.clone(),
+            });
+        }
+        self.virtual_time = finish;
+        self.len -= 1;
+        Some((head.flow, item))
+    }
+
+    pub fn virtual_time(&self) -> f64 {
+        self.virtual_time
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<F, T> Default for FairQueue<F, T>
+where
+    F: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
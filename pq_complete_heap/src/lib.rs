@@ -1,2 +1,30 @@
-mod pq_complete_heap;
+macro_rules! parent {
+    ($i:expr) => {
+        ($i - 1) >> 1
+    };
+}
+
+macro_rules! l_child {
+    ($i:expr) => {
+        ($i << 1) + 1
+    };
+}
+
+macro_rules! r_child {
+    ($i:expr) => {
+        ($i + 1) << 1
+    };
+}
+
+pub mod executor;
+pub mod fair_queue;
+pub mod observer;
+pub mod pq_complete_heap;
+pub mod priority_map;
+pub mod render;
+pub mod sim;
+pub mod snapshot;
+#[allow(clippy::module_inception, clippy::redundant_closure)]
 mod tests;
+pub mod unique_heap;
+pub mod window;
@@ -0,0 +1,137 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapOp {
+    Push,
+    Pop,
+    Remove,
+    Heapify,
+}
+
+pub trait HeapObserver {
+    #[inline]
+    fn begin(&mut self, _op: HeapOp) {}
+
+    #[inline]
+    fn end(&mut self, _op: HeapOp) {}
+
+    #[inline]
+    fn compare(&mut self, _i: usize, _j: usize) {}
+
+    #[inline]
+    fn swap(&mut self, _i: usize, _j: usize) {}
+
+    #[inline]
+    fn sift_up(&mut self, _depth: usize) {}
+
+    #[inline]
+    fn sift_down(&mut self, _depth: usize) {}
+}
+
+impl HeapObserver for () {}
+
+impl<O> HeapObserver for &mut O
+where
+    O: HeapObserver,
+{
+    #[inline]
+    fn begin(&mut self, op: HeapOp) {
+        (**self).begin(op)
+    }
+
+    #[inline]
+    fn end(&mut self, op: HeapOp) {
+        (**self).end(op)
+    }
+
+    #[inline]
+    fn compare(&mut self, i: usize, j: usize) {
+        (**self).compare(i, j)
+    }
+
+    #[inline]
+    fn swap(&mut self, i: usize, j: usize) {
+        (**self).swap(i, j)
+    }
+
+    #[inline]
+    fn sift_up(&mut self, depth: usize) {
+        (**self).sift_up(depth)
+    }
+
+    #[inline]
+    fn sift_down(&mut self, depth: usize) {
+        (**self).sift_down(depth)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    pub comparisons: usize,
+    pub swaps: usize,
+    pub sift_depth: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub pushes: usize,
+    pub pops: usize,
+    pub removes: usize,
+    pub heapifies: usize,
+    pub total: OpCounts,
+    pub last: OpCounts,
+    pub worst: OpCounts,
+    pub max_sift_depth: usize,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl HeapObserver for Stats {
+    fn begin(&mut self, op: HeapOp) {
+        match op {
+            HeapOp::Push => self.pushes += 1,
+            HeapOp::Pop => self.pops += 1,
+            HeapOp::Remove => self.removes += 1,
+            HeapOp::Heapify => self.heapifies += 1,
+        }
+        self.last = OpCounts::default();
+    }
+
+    fn end(&mut self, _op: HeapOp) {
+        self.worst.comparisons = self.worst.comparisons.max(self.last.comparisons);
+        self.worst.swaps = self.worst.swaps.max(self.last.swaps);
+        self.worst.sift_depth = self.worst.sift_depth.max(self.last.sift_depth);
+    }
+
+    fn compare(&mut self, _i: usize, _j: usize) {
+        self.total.comparisons += 1;
+        self.last.comparisons += 1;
+    }
+
+    fn swap(&mut self, _i: usize, _j: usize) {
+        self.total.swaps += 1;
+        self.last.swaps += 1;
+    }
+
+    fn sift_up(&mut self, depth: usize) {
+        self.record_sift(depth);
+    }
+
+    fn sift_down(&mut self, depth: usize) {
+        self.record_sift(depth);
+    }
+}
+
+impl Stats {
+    fn record_sift(&mut self, depth: usize) {
+        self.total.sift_depth += depth;
+        self.last.sift_depth += depth;
+        self.max_sift_depth = self.max_sift_depth.max(depth);
+    }
+}
@@ -1,29 +1,69 @@
 use std::cmp::Ordering::Less;
+use std::collections::BTreeMap;
 
-macro_rules! parent {
-    ($i:expr) => {
-        ($i - 1) >> 1
-    };
-}
-
-macro_rules! l_child {
-    ($i:expr) => {
-        ($i << 1) + 1
-    };
-}
-
-macro_rules! r_child {
-    ($i:expr) => {
-        ($i + 1) << 1
-    };
-}
+use crate::observer::{HeapObserver, HeapOp};
 
 #[derive(Debug)]
-pub struct PqCompleteHeap<T>
+pub struct PqCompleteHeap<T, O = ()>
 where
     T: Ord,
 {
     elem: Vec<T>,
+    deleted: BTreeMap<T, usize>,
+    tombstones: usize,
+    live: Option<LiveCounts<T>>,
+    observer: O,
+}
+
+// Copies of each value that are stored and not tombstoned, so `remove` can
+// tell in O(log n) whether a value is present. Keys are cloned in, which
+// `push` cannot demand of every `T`, so the counts only exist once `remove`
+// has supplied `T::clone`; `peek_mut` drops them because the root may be
+// rewritten, and the next `remove` recounts.
+#[derive(Debug)]
+struct LiveCounts<T> {
+    counts: BTreeMap<T, usize>,
+    clone: fn(&T) -> T,
+}
+
+impl<T: Ord> LiveCounts<T> {
+    fn new(elem: &[T], deleted: &BTreeMap<T, usize>, clone: fn(&T) -> T) -> Self {
+        let mut live = LiveCounts {
+            counts: BTreeMap::new(),
+            clone,
+        };
+        for e in elem {
+            live.add(e);
+        }
+        for (e, &n) in deleted {
+            live.take(e, n);
+        }
+        live
+    }
+
+    fn add(&mut self, e: &T) {
+        match self.counts.get_mut(e) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert((self.clone)(e), 1);
+            }
+        }
+    }
+
+    // Returns false, and changes nothing, if fewer than `n` copies are live.
+    fn take(&mut self, e: &T, n: usize) -> bool {
+        let Some(count) = self.counts.get_mut(e) else {
+            return n == 0;
+        };
+        if *count < n {
+            return false;
+        }
+        *count -= n;
+        if *count == 0 {
+            self.counts.remove(e);
+        }
+        true
+    }
 }
 
 #[allow(dead_code)]
@@ -33,22 +73,233 @@ where
 {
     #[inline]
     pub fn new() -> Self {
-        PqCompleteHeap { elem: vec![] }
+        Self::with_observer(())
     }
 
     #[inline]
     pub fn new_with_vec(elem: Vec<T>) -> Self {
-        let mut pq = Self { elem };
+        Self::new_with_vec_observed(elem, ())
+    }
+
+    pub(crate) fn from_raw(elem: Vec<T>) -> Self {
+        Self {
+            elem,
+            deleted: BTreeMap::new(),
+            tombstones: 0,
+            live: None,
+            observer: (),
+        }
+    }
+
+    pub fn new_with_vec_parallel(mut elem: Vec<T>, threads: usize) -> Self
+    where
+        T: Send,
+    {
+        let split = threads.next_power_of_two().trailing_zeros() as usize;
+        let depth = (usize::BITS - elem.len().leading_zeros()) as usize;
+        if threads <= 1 || depth <= split + 1 {
+            return Self::new_with_vec(elem);
+        }
+
+        // Every level below `split` is cut into one contiguous chunk per
+        // subtree, so each worker owns its subtrees outright.
+        let mut subtrees: Vec<Vec<&mut [T]>> = (0..1 << split).map(|_| vec![]).collect();
+        let mut rest = &mut elem[(1 << split) - 1..];
+        for level in split.. {
+            if rest.is_empty() {
+                break;
+            }
+            let width = (1 << level).min(rest.len());
+            let (row, tail) = rest.split_at_mut(width);
+            for (k, chunk) in row.chunks_mut(1 << (level - split)).enumerate() {
+                subtrees[k].push(chunk);
+            }
+            rest = tail;
+        }
+
+        let mut workers: Vec<Vec<Vec<&mut [T]>>> = (0..threads).map(|_| vec![]).collect();
+        for (k, subtree) in subtrees.into_iter().enumerate() {
+            workers[k % threads].push(subtree);
+        }
+        std::thread::scope(|scope| {
+            for work in workers {
+                scope.spawn(move || {
+                    for mut levels in work {
+                        heapify_levels(&mut levels);
+                    }
+                });
+            }
+        });
+
+        let mut pq = Self::from_raw(elem);
+        for i in (0..(1 << split) - 1).rev() {
+            pq.percolate_down(i);
+        }
+        pq.debug_validate();
+        pq
+    }
+}
+
+// Sift loops shared with `PriorityMap`: `on_swap` runs after every swap so a
+// caller can keep side tables in step with the array.
+pub(crate) fn sift_up<T, O, F>(
+    elem: &mut [T],
+    mut i: usize,
+    observer: &mut O,
+    mut on_swap: F,
+) -> usize
+where
+    T: Ord,
+    O: HeapObserver,
+    F: FnMut(&[T], usize, usize),
+{
+    let mut depth = 0;
+    while i > 0 {
+        let j = parent!(i);
+        observer.compare(j, i);
+        if elem[j].cmp(&elem[i]) != Less {
+            break;
+        }
+        observer.swap(i, j);
+        elem.swap(i, j);
+        on_swap(elem, i, j);
+        i = j;
+        depth += 1;
+    }
+    observer.sift_up(depth);
+    i
+}
+
+pub(crate) fn sift_down<T, O, F>(
+    elem: &mut [T],
+    mut i: usize,
+    observer: &mut O,
+    mut on_swap: F,
+) -> usize
+where
+    T: Ord,
+    O: HeapObserver,
+    F: FnMut(&[T], usize, usize),
+{
+    let mut depth = 0;
+    let size = elem.len();
+    loop {
+        let mut largest = i;
+        let (left_child, right_child) = (l_child!(i), r_child!(i));
+
+        if left_child < size {
+            observer.compare(left_child, largest);
+            if elem[left_child].cmp(&elem[largest]) != Less {
+                largest = left_child;
+            }
+        }
+        if right_child < size {
+            observer.compare(right_child, largest);
+            if elem[right_child].cmp(&elem[largest]) != Less {
+                largest = right_child;
+            }
+        }
+
+        if largest == i {
+            break;
+        }
+        observer.swap(i, largest);
+        elem.swap(i, largest);
+        on_swap(elem, i, largest);
+        i = largest;
+        depth += 1;
+    }
+    observer.sift_down(depth);
+    i
+}
+
+fn heapify_levels<T: Ord>(levels: &mut [&mut [T]]) {
+    for level in (0..levels.len().saturating_sub(1)).rev() {
+        for i in (0..levels[level].len()).rev() {
+            percolate_down_levels(levels, level, i);
+        }
+    }
+}
+
+fn percolate_down_levels<T: Ord>(levels: &mut [&mut [T]], mut level: usize, mut i: usize) {
+    while level + 1 < levels.len() {
+        let (upper, lower) = levels.split_at_mut(level + 1);
+        let (node, children) = (&mut upper[level][i], &mut lower[0]);
+        let (left_child, right_child) = (i << 1, (i << 1) + 1);
+
+        let mut largest = None;
+        if left_child < children.len() && children[left_child].cmp(node) != Less {
+            largest = Some(left_child);
+        }
+        if right_child < children.len() {
+            let current = largest.map_or(&*node, |c| &children[c]);
+            if children[right_child].cmp(current) != Less {
+                largest = Some(right_child);
+            }
+        }
+
+        match largest {
+            Some(c) => {
+                std::mem::swap(node, &mut children[c]);
+                level += 1;
+                i = c;
+            }
+            None => break,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T, O> PqCompleteHeap<T, O>
+where
+    T: Ord,
+    O: HeapObserver,
+{
+    #[inline]
+    pub fn with_observer(observer: O) -> Self {
+        PqCompleteHeap {
+            elem: vec![],
+            deleted: BTreeMap::new(),
+            tombstones: 0,
+            live: None,
+            observer,
+        }
+    }
+
+    #[inline]
+    pub fn new_with_vec_observed(elem: Vec<T>, observer: O) -> Self {
+        let mut pq = Self {
+            elem,
+            deleted: BTreeMap::new(),
+            tombstones: 0,
+            live: None,
+            observer,
+        };
         pq.heapify();
         pq
     }
 
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
     pub fn push(&mut self, e: T) {
+        self.observer.begin(HeapOp::Push);
+        if let Some(live) = &mut self.live {
+            live.add(&e);
+        }
         self.elem.push(e);
-        self.percolate_up(self.elem.len() - 1);
+        let i = self.percolate_up(self.elem.len() - 1);
+        self.observer.end(HeapOp::Push);
+        self.debug_validate_path(i);
     }
 
     pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.live = None;
         self.elem.first_mut()
     }
 
@@ -57,69 +308,191 @@ where
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        if !self.elem.is_empty() {
-            let size = self.elem.len();
-            self.elem.swap(0, size - 1);
-            let e = self.elem.pop();
-            self.percolate_down(0);
-            e
-        } else {
-            None
+        if self.elem.is_empty() {
+            return None;
+        }
+        self.observer.begin(HeapOp::Pop);
+        let e = self.take_top();
+        if let (Some(live), Some(e)) = (&mut self.live, &e) {
+            live.take(e, 1);
         }
+        self.purge();
+        self.observer.end(HeapOp::Pop);
+        e
+    }
+
+    // Lazily deletes one element equal to `e`. Returns false, and leaves the
+    // heap alone, if every copy of `e` is already gone.
+    pub fn remove(&mut self, e: &T) -> bool
+    where
+        T: Clone,
+    {
+        let live = self
+            .live
+            .get_or_insert_with(|| LiveCounts::new(&self.elem, &self.deleted, T::clone));
+        if !live.take(e, 1) {
+            return false;
+        }
+
+        self.observer.begin(HeapOp::Remove);
+        *self.deleted.entry(e.clone()).or_insert(0) += 1;
+        self.tombstones += 1;
+        self.purge();
+        if self.tombstones > self.len() {
+            self.compact();
+        }
+        self.observer.end(HeapOp::Remove);
+        true
     }
 
     pub fn len(&self) -> usize {
-        self.elem.len()
+        self.elem.len() - self.tombstones
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The backing array as stored, so it still holds lazily removed
+    // elements that have not reached the top yet; its length can exceed
+    // `len()`.
+    pub fn as_slice(&self) -> &[T] {
+        &self.elem
+    }
+
+    pub(crate) fn tombstones(&self) -> &BTreeMap<T, usize> {
+        &self.deleted
+    }
+
+    pub fn validate(&self) -> Result<(), usize> {
+        match (1..self.elem.len()).find(|&i| self.elem[parent!(i)] < self.elem[i]) {
+            Some(i) => Err(i),
+            None => Ok(()),
+        }
+    }
+
+    pub fn repair(&mut self) -> bool {
+        if self.validate().is_ok() {
+            return false;
+        }
+        self.heapify();
+        true
     }
 }
 
-impl<T> PqCompleteHeap<T>
+impl<T, O> Default for PqCompleteHeap<T, O>
 where
     T: Ord,
+    O: HeapObserver + Default,
 {
-    fn percolate_up(&mut self, mut i: usize) {
-        while i > 0 {
-            let j = parent!(i);
-            if self.elem[j].cmp(&self.elem[i]) != Less {
-                break;
-            }
-            self.elem.swap(i, j);
-            i = j;
-        }
+    fn default() -> Self {
+        Self::with_observer(O::default())
     }
+}
 
-    fn percolate_down(&mut self, mut i: usize) {
-        loop {
-            let mut largest = i;
-            let size = self.elem.len();
-            let (left_child, right_child) = (l_child!(largest), r_child!(largest));
+impl<T, O> PqCompleteHeap<T, O>
+where
+    T: Ord,
+    O: HeapObserver,
+{
+    fn take_top(&mut self) -> Option<T> {
+        let size = self.elem.len();
+        if size == 0 {
+            return None;
+        }
+        self.swap(0, size - 1);
+        let e = self.elem.pop();
+        let i = self.percolate_down(0);
+        self.debug_validate_path(i);
+        e
+    }
 
-            largest = if left_child < size && self.elem[left_child].cmp(&self.elem[largest]) != Less
-            {
-                left_child
-            } else {
-                largest
+    // Drops tombstoned elements off the top. Runs inside the caller's
+    // pop/remove bracket, so observers never see it as an operation of its own.
+    fn purge(&mut self) {
+        while let Some(top) = self.elem.first() {
+            let Some(count) = self.deleted.get_mut(top) else {
+                break;
             };
+            *count -= 1;
+            if *count == 0 {
+                self.deleted.remove(top);
+            }
+            self.tombstones -= 1;
+            self.take_top();
+        }
+    }
 
-            largest =
-                if right_child < size && self.elem[right_child].cmp(&self.elem[largest]) != Less {
-                    right_child
-                } else {
-                    largest
-                };
-
-            if largest != i {
-                self.elem.swap(i, largest);
-                i = largest;
-            } else {
-                break;
+    // Tombstones below the top are only dropped when they surface, so once
+    // they outnumber the live elements they are filtered out in one pass and
+    // the rest re-heapified. Storage stays within twice the live count.
+    fn compact(&mut self) {
+        let deleted = &mut self.deleted;
+        self.elem.retain(|e| match deleted.get_mut(e) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
             }
+            _ => true,
+        });
+        self.deleted.clear();
+        self.tombstones = 0;
+        for i in (0..self.elem.len() >> 1).rev() {
+            self.percolate_down(i);
         }
+        self.debug_validate();
+    }
+
+    #[inline]
+    fn swap(&mut self, i: usize, j: usize) {
+        self.observer.swap(i, j);
+        self.elem.swap(i, j);
+    }
+
+    fn percolate_up(&mut self, i: usize) -> usize {
+        sift_up(&mut self.elem, i, &mut self.observer, |_, _, _| {})
+    }
+
+    fn percolate_down(&mut self, i: usize) -> usize {
+        sift_down(&mut self.elem, i, &mut self.observer, |_, _, _| {})
     }
 
     fn heapify(&mut self) {
-        for i in (0..=((self.elem.len() >> 1) - 1)).rev() {
+        self.observer.begin(HeapOp::Heapify);
+        for i in (0..self.elem.len() >> 1).rev() {
             self.percolate_down(i);
         }
+        self.purge();
+        self.observer.end(HeapOp::Heapify);
+        self.debug_validate();
+    }
+
+    #[inline]
+    fn debug_validate(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(i) = self.validate() {
+                panic!("heap property violated at index {}", i);
+            }
+        }
+    }
+
+    // A sift only moves elements along the path from where it stopped to the
+    // root, so checking the children of each node on that path covers every
+    // swapped index without scanning the whole array.
+    #[inline]
+    fn debug_validate_path(&self, mut i: usize) {
+        if cfg!(debug_assertions) && i < self.elem.len() {
+            loop {
+                for child in [l_child!(i), r_child!(i)] {
+                    if child < self.elem.len() && self.elem[i] < self.elem[child] {
+                        panic!("heap property violated at index {}", child);
+                    }
+                }
+                if i == 0 {
+                    break;
+                }
+                i = parent!(i);
+            }
+        }
     }
 }
@@ -0,0 +1,184 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::pq_complete_heap::{sift_down, sift_up};
+
+#[derive(Debug)]
+struct Entry<K, P> {
+    key: Arc<K>,
+    priority: P,
+}
+
+impl<K, P: Ord> PartialEq for Entry<K, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<K, P: Ord> Eq for Entry<K, P> {}
+
+impl<K, P: Ord> PartialOrd for Entry<K, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, P: Ord> Ord for Entry<K, P> {
+    // ordered by priority alone; keys only need Hash + Eq
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+#[derive(Debug)]
+pub struct PriorityMap<K, P>
+where
+    K: Hash + Eq,
+    P: Ord,
+{
+    elem: Vec<Entry<K, P>>,
+    index: HashMap<Arc<K>, usize>,
+}
+
+impl<K, P> PriorityMap<K, P>
+where
+    K: Hash + Eq,
+    P: Ord,
+{
+    #[inline]
+    pub fn new() -> Self {
+        PriorityMap {
+            elem: vec![],
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, key: K, priority: P) -> Option<P> {
+        match self.index.get(&key) {
+            Some(&i) => Some(self.replace_at(i, priority)),
+            None => {
+                self.push_new(key, priority);
+                None
+            }
+        }
+    }
+
+    pub fn push_increase(&mut self, key: K, priority: P) -> Option<P> {
+        match self.index.get(&key) {
+            Some(&i) if self.elem[i].priority < priority => Some(self.replace_at(i, priority)),
+            Some(_) => Some(priority),
+            None => {
+                self.push_new(key, priority);
+                None
+            }
+        }
+    }
+
+    pub fn change_priority(&mut self, key: &K, priority: P) -> Option<P> {
+        let i = *self.index.get(key)?;
+        Some(self.replace_at(i, priority))
+    }
+
+    pub fn get_priority(&self, key: &K) -> Option<&P> {
+        self.index.get(key).map(|&i| &self.elem[i].priority)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<(K, P)> {
+        let i = *self.index.get(key)?;
+        Some(self.remove_at(i))
+    }
+
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.elem
+            .first()
+            .map(|entry| (&*entry.key, &entry.priority))
+    }
+
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        if self.elem.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elem.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elem.is_empty()
+    }
+}
+
+impl<K, P> Default for PriorityMap<K, P>
+where
+    K: Hash + Eq,
+    P: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, P> PriorityMap<K, P>
+where
+    K: Hash + Eq,
+    P: Ord,
+{
+    fn push_new(&mut self, key: K, priority: P) {
+        let key = Arc::new(key);
+        let i = self.elem.len();
+        self.index.insert(Arc::clone(&key), i);
+        self.elem.push(Entry { key, priority });
+        self.percolate_up(i);
+    }
+
+    fn replace_at(&mut self, i: usize, priority: P) -> P {
+        let old = std::mem::replace(&mut self.elem[i].priority, priority);
+        let i = self.percolate_up(i);
+        self.percolate_down(i);
+        old
+    }
+
+    fn remove_at(&mut self, i: usize) -> (K, P) {
+        let last = self.elem.len() - 1;
+        self.elem.swap(i, last);
+        let Entry { key, priority } = self.elem.pop().unwrap();
+        self.index.remove(&key);
+        if i < self.elem.len() {
+            *self.index.get_mut(&self.elem[i].key).unwrap() = i;
+            let i = self.percolate_up(i);
+            self.percolate_down(i);
+        }
+        match Arc::try_unwrap(key) {
+            Ok(key) => (key, priority),
+            Err(_) => unreachable!("key is shared only with the index"),
+        }
+    }
+
+    fn percolate_up(&mut self, i: usize) -> usize {
+        let index = &mut self.index;
+        sift_up(&mut self.elem, i, &mut (), |elem, i, j| {
+            Self::reindex(index, elem, i, j)
+        })
+    }
+
+    fn percolate_down(&mut self, i: usize) -> usize {
+        let index = &mut self.index;
+        sift_down(&mut self.elem, i, &mut (), |elem, i, j| {
+            Self::reindex(index, elem, i, j)
+        })
+    }
+
+    fn reindex(index: &mut HashMap<Arc<K>, usize>, elem: &[Entry<K, P>], i: usize, j: usize) {
+        *index.get_mut(&elem[i].key).unwrap() = i;
+        *index.get_mut(&elem[j].key).unwrap() = j;
+    }
+}
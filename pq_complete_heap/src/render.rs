@@ -0,0 +1,82 @@
+use std::fmt::{self, Debug, Display, Write};
+
+use crate::observer::HeapObserver;
+use crate::pq_complete_heap::PqCompleteHeap;
+
+impl<T, O> PqCompleteHeap<T, O>
+where
+    T: Ord + Debug,
+    O: HeapObserver,
+{
+    // Draws the backing array, lazily removed elements included.
+    pub fn to_dot(&self, highlight: &[usize]) -> String {
+        let elem = self.as_slice();
+        let mut dot = String::from("digraph heap {\n    node [shape=circle];\n");
+
+        for (i, e) in elem.iter().enumerate() {
+            let label = escape(&format!("{:?}", e));
+            if highlight.contains(&i) {
+                writeln!(
+                    dot,
+                    "    n{} [label=\"{}\", style=filled, fillcolor=lightcoral];",
+                    i, label
+                )
+                .unwrap();
+            } else {
+                writeln!(dot, "    n{} [label=\"{}\"];", i, label).unwrap();
+            }
+        }
+
+        for i in 0..elem.len() {
+            for child in [l_child!(i), r_child!(i)] {
+                if child < elem.len() {
+                    writeln!(dot, "    n{} -> n{};", i, child).unwrap();
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_subtree(&self, f: &mut fmt::Formatter<'_>, i: usize, prefix: &str) -> fmt::Result {
+        let elem = self.as_slice();
+        let children: Vec<usize> = [l_child!(i), r_child!(i)]
+            .into_iter()
+            .filter(|&c| c < elem.len())
+            .collect();
+
+        for (k, &child) in children.iter().enumerate() {
+            let last = k + 1 == children.len();
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            writeln!(f, "{}{}{:?}", prefix, branch, elem[child])?;
+            self.write_subtree(f, child, &format!("{}{}", prefix, indent))?;
+        }
+        Ok(())
+    }
+}
+
+// Like `to_dot`, shows every stored element, including lazily removed ones.
+impl<T, O> Display for PqCompleteHeap<T, O>
+where
+    T: Ord + Debug,
+    O: HeapObserver,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.as_slice().first() {
+            None => writeln!(f, "(empty)"),
+            Some(root) => {
+                writeln!(f, "{:?}", root)?;
+                self.write_subtree(f, 0, "")
+            }
+        }
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
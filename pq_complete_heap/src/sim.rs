@@ -0,0 +1,244 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+
+use crate::pq_complete_heap::PqCompleteHeap;
+
+pub type Time = f64;
+
+type Handler<S> = Box<dyn FnOnce(&mut Simulation<S>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventId(u64);
+
+#[derive(Debug, Clone, Copy)]
+struct Scheduled {
+    time: Time,
+    seq: u64,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    // earliest time first, then first scheduled first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .total_cmp(&self.time)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn exp(&mut self, rate: f64) -> f64 {
+        -(1.0 - self.next_f64()).ln() / rate
+    }
+}
+
+pub struct Simulation<S> {
+    now: Time,
+    seq: u64,
+    queue: PqCompleteHeap<Scheduled>,
+    handlers: HashMap<u64, (Time, Handler<S>)>,
+    rng: Rng,
+    state: S,
+}
+
+impl<S> Simulation<S> {
+    pub fn new(state: S, seed: u64) -> Self {
+        Simulation {
+            now: 0.0,
+            seq: 0,
+            queue: PqCompleteHeap::new(),
+            handlers: HashMap::new(),
+            rng: Rng::new(seed),
+            state,
+        }
+    }
+
+    pub fn now(&self) -> Time {
+        self.now
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    pub fn pending(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn schedule_at<F>(&mut self, time: Time, handler: F) -> EventId
+    where
+        F: FnOnce(&mut Simulation<S>) + 'static,
+    {
+        assert!(time >= self.now, "cannot schedule an event in the past");
+        let seq = self.seq;
+        self.seq += 1;
+        self.queue.push(Scheduled { time, seq });
+        self.handlers.insert(seq, (time, Box::new(handler)));
+        EventId(seq)
+    }
+
+    pub fn schedule_in<F>(&mut self, delay: Time, handler: F) -> EventId
+    where
+        F: FnOnce(&mut Simulation<S>) + 'static,
+    {
+        self.schedule_at(self.now + delay, handler)
+    }
+
+    // Cancelled events are removed from the queue as well, so models that
+    // arm and cancel timeouts do not pile up dead entries.
+    pub fn cancel(&mut self, id: EventId) -> bool {
+        match self.handlers.remove(&id.0) {
+            Some((time, _)) => self.queue.remove(&Scheduled { time, seq: id.0 }),
+            None => false,
+        }
+    }
+
+    pub fn step(&mut self) -> bool {
+        let Some(event) = self.queue.pop() else {
+            return false;
+        };
+        let (_, handler) = self
+            .handlers
+            .remove(&event.seq)
+            .expect("queued event has no handler");
+        self.now = event.time;
+        handler(self);
+        true
+    }
+
+    pub fn run_until(&mut self, time: Time) -> usize {
+        let mut fired = 0;
+        while self.queue.peek().is_some_and(|event| event.time <= time) {
+            self.step();
+            fired += 1;
+        }
+        if time > self.now {
+            self.now = time;
+        }
+        fired
+    }
+
+    // Entries stored in the queue, including cancelled ones it has not
+    // dropped yet.
+    pub fn queued(&self) -> usize {
+        self.queue.as_slice().len()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Mm1 {
+    in_system: usize,
+    arrivals: VecDeque<Time>,
+    last_change: Time,
+    area: f64,
+    busy: f64,
+    completed: usize,
+    sojourn: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Mm1Report {
+    pub mean_in_system: f64,
+    pub mean_sojourn: f64,
+    pub utilization: f64,
+    pub completed: usize,
+}
+
+impl Mm1 {
+    fn advance(&mut self, now: Time) {
+        let dt = now - self.last_change;
+        self.area += dt * self.in_system as f64;
+        if self.in_system > 0 {
+            self.busy += dt;
+        }
+        self.last_change = now;
+    }
+}
+
+pub fn mm1(arrival_rate: f64, service_rate: f64, horizon: Time, seed: u64) -> Mm1Report {
+    fn arrive(sim: &mut Simulation<Mm1>, arrival_rate: f64, service_rate: f64) {
+        let now = sim.now();
+        let mm1 = sim.state_mut();
+        mm1.advance(now);
+        mm1.in_system += 1;
+        mm1.arrivals.push_back(now);
+        if mm1.in_system == 1 {
+            let service = sim.rng().exp(service_rate);
+            sim.schedule_in(service, move |sim| depart(sim, service_rate));
+        }
+        let gap = sim.rng().exp(arrival_rate);
+        sim.schedule_in(gap, move |sim| arrive(sim, arrival_rate, service_rate));
+    }
+
+    fn depart(sim: &mut Simulation<Mm1>, service_rate: f64) {
+        let now = sim.now();
+        let mm1 = sim.state_mut();
+        mm1.advance(now);
+        mm1.in_system -= 1;
+        mm1.completed += 1;
+        mm1.sojourn += now - mm1.arrivals.pop_front().unwrap();
+        if mm1.in_system > 0 {
+            let service = sim.rng().exp(service_rate);
+            sim.schedule_in(service, move |sim| depart(sim, service_rate));
+        }
+    }
+
+    let mut sim = Simulation::new(Mm1::default(), seed);
+    let gap = sim.rng().exp(arrival_rate);
+    sim.schedule_in(gap, move |sim| arrive(sim, arrival_rate, service_rate));
+    sim.run_until(horizon);
+    sim.state_mut().advance(horizon);
+
+    let mm1 = sim.into_state();
+    Mm1Report {
+        mean_in_system: mm1.area / horizon,
+        mean_sojourn: mm1.sojourn / mm1.completed as f64,
+        utilization: mm1.busy / horizon,
+        completed: mm1.completed,
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufWriter, Read, Write};
+
+use crate::observer::HeapObserver;
+use crate::pq_complete_heap::PqCompleteHeap;
+
+const MAGIC: [u8; 4] = *b"PQCH";
+const VERSION: u16 = 1;
+
+pub trait HeapCodec<T> {
+    fn encode(&self, value: &T, out: &mut Vec<u8>);
+
+    fn decode(&self, bytes: &[u8]) -> Option<T>;
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Truncated,
+    BadMagic,
+    UnsupportedVersion(u16),
+    ChecksumMismatch { stored: u64, computed: u64 },
+    Element(usize),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "i/o error: {}", e),
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::BadMagic => write!(f, "not a heap snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {}", v)
+            }
+            SnapshotError::ChecksumMismatch { stored, computed } => write!(
+                f,
+                "checksum mismatch: stored {:#018x}, computed {:#018x}",
+                stored, computed
+            ),
+            SnapshotError::Element(i) => write!(f, "element {} could not be decoded", i),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            SnapshotError::Truncated
+        } else {
+            SnapshotError::Io(e)
+        }
+    }
+}
+
+// FNV-1a over every byte preceding the trailing checksum.
+struct Checksum(u64);
+
+impl Checksum {
+    fn new() -> Self {
+        Checksum(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+struct Writer<W> {
+    inner: W,
+    checksum: Checksum,
+}
+
+impl<W: Write> Writer<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.checksum.update(bytes);
+        self.inner.write_all(bytes)
+    }
+}
+
+struct Reader<R> {
+    inner: R,
+    checksum: Checksum,
+}
+
+impl<R: Read> Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), SnapshotError> {
+        self.inner.read_exact(buf)?;
+        self.checksum.update(buf);
+        Ok(())
+    }
+
+    fn read_bytes(&mut self, size: usize, bytes: &mut Vec<u8>) -> Result<(), SnapshotError> {
+        bytes.clear();
+        (&mut self.inner).take(size as u64).read_to_end(bytes)?;
+        if bytes.len() != size {
+            return Err(SnapshotError::Truncated);
+        }
+        self.checksum.update(bytes);
+        Ok(())
+    }
+
+    fn read_u16(&mut self) -> Result<u16, SnapshotError> {
+        let mut buf = [0; 2];
+        self.read(&mut buf)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, SnapshotError> {
+        let mut buf = [0; 4];
+        self.read(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, SnapshotError> {
+        let mut buf = [0; 8];
+        self.read(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+impl<T, O> PqCompleteHeap<T, O>
+where
+    T: Ord,
+    O: HeapObserver,
+{
+    // Elements are encoded and written one at a time through a buffer, so
+    // saving needs no copy of the heap.
+    pub fn save_to<W, C>(&self, writer: W, codec: &C) -> io::Result<()>
+    where
+        W: Write,
+        C: HeapCodec<T>,
+    {
+        let mut writer = Writer {
+            inner: BufWriter::new(writer),
+            checksum: Checksum::new(),
+        };
+        writer.write(&MAGIC)?;
+        writer.write(&VERSION.to_le_bytes())?;
+        writer.write(&(self.len() as u64).to_le_bytes())?;
+
+        let mut tombstones = self
+            .tombstones()
+            .iter()
+            .map(|(e, &count)| (e, count))
+            .collect::<BTreeMap<&T, usize>>();
+        let mut encoded = Vec::new();
+        for e in self.as_slice() {
+            if let Some(count) = tombstones.get_mut(e).filter(|count| **count > 0) {
+                *count -= 1;
+                continue;
+            }
+            encoded.clear();
+            codec.encode(e, &mut encoded);
+            let len = u32::try_from(encoded.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "element too large"))?;
+            writer.write(&len.to_le_bytes())?;
+            writer.write(&encoded)?;
+        }
+
+        let checksum = writer.checksum.0;
+        writer.inner.write_all(&checksum.to_le_bytes())?;
+        writer.inner.flush()
+    }
+}
+
+impl<T> PqCompleteHeap<T>
+where
+    T: Ord,
+{
+    pub fn load_from<R, C>(reader: R, codec: &C) -> Result<Self, SnapshotError>
+    where
+        R: Read,
+        C: HeapCodec<T>,
+    {
+        let mut reader = Reader {
+            inner: reader,
+            checksum: Checksum::new(),
+        };
+
+        let mut magic = [0; 4];
+        reader.read(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = reader.read_u16()?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        // Elements are decoded as they arrive. A decode failure is only
+        // reported once the checksum has ruled out corruption, so the rest
+        // is still read, just not decoded.
+        let len = reader.read_u64()?;
+        let mut elem = Vec::with_capacity(len.min(1 << 16) as usize);
+        let mut failed = None;
+        let mut bytes = Vec::new();
+        for i in 0..len as usize {
+            let size = reader.read_u32()? as usize;
+            reader.read_bytes(size, &mut bytes)?;
+            if failed.is_none() {
+                match codec.decode(&bytes) {
+                    Some(e) => elem.push(e),
+                    None => failed = Some(i),
+                }
+            }
+        }
+
+        let computed = reader.checksum.0;
+        let mut stored = [0; 8];
+        reader.inner.read_exact(&mut stored)?;
+        let stored = u64::from_le_bytes(stored);
+        if stored != computed {
+            return Err(SnapshotError::ChecksumMismatch { stored, computed });
+        }
+        if let Some(i) = failed {
+            return Err(SnapshotError::Element(i));
+        }
+
+        let mut pq = Self::from_raw(elem);
+        pq.repair();
+        Ok(pq)
+    }
+}
@@ -2,7 +2,15 @@
 mod tests {
     use std::cmp::Reverse;
 
+    use crate::executor::{JoinError, PriorityExecutor, Shutdown};
+    use crate::fair_queue::FairQueue;
+    use crate::observer::{HeapObserver, Stats};
     use crate::pq_complete_heap::PqCompleteHeap;
+    use crate::priority_map::PriorityMap;
+    use crate::sim::{mm1, Rng, Simulation};
+    use crate::snapshot::{HeapCodec, SnapshotError};
+    use crate::unique_heap::UniqueHeap;
+    use crate::window::WindowedExtreme;
 
     #[test]
     fn test_new() {
@@ -64,4 +72,683 @@ mod tests {
         }
         assert_eq!(a.pop(), None);
     }
+
+    #[test]
+    fn test_new_empty() {
+        let mut a = PqCompleteHeap::new_with_vec(Vec::<i32>::new());
+        assert_eq!(a.pop(), None);
+        let mut b = PqCompleteHeap::new_with_vec(vec![1]);
+        assert_eq!(b.pop(), Some(1));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut a = PqCompleteHeap::with_observer(Stats::new());
+        for i in 0..8 {
+            a.push(i);
+        }
+        assert_eq!(a.observer().pushes, 8);
+        // ascending input sifts every element all the way to the root
+        assert_eq!(a.observer().total.sift_depth, 1 + 1 + 2 + 2 + 2 + 2 + 3);
+        assert_eq!(a.observer().total.swaps, a.observer().total.sift_depth);
+        assert_eq!(a.observer().max_sift_depth, 3);
+        assert_eq!(a.observer().worst.comparisons, 3);
+
+        a.observer_mut().reset();
+        assert_eq!(a.pop(), Some(7));
+        let stats = a.observer();
+        assert_eq!(stats.pops, 1);
+        assert_eq!(stats.last, stats.total);
+        assert!(stats.last.comparisons <= 2 * (stats.last.sift_depth + 1));
+    }
+
+    #[test]
+    fn test_observer_heapify() {
+        #[derive(Default)]
+        struct Log(Vec<(usize, usize)>);
+
+        impl HeapObserver for Log {
+            fn swap(&mut self, i: usize, j: usize) {
+                self.0.push((i, j));
+            }
+        }
+
+        let mut log = Log::default();
+        let a = PqCompleteHeap::new_with_vec_observed(vec![1, 2, 3], &mut log);
+        assert_eq!(a.peek(), Some(&3));
+        drop(a);
+        assert_eq!(log.0, vec![(0, 2)]);
+
+        let n = 1 << 10;
+        let mut stats = Stats::new();
+        PqCompleteHeap::new_with_vec_observed((0..n).collect(), &mut stats);
+        assert_eq!(stats.heapifies, 1);
+        assert!(stats.total.swaps < n);
+        assert!(stats.total.comparisons <= 2 * n);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let a = PqCompleteHeap::new_with_vec(vec![1, 2, 3]);
+        assert_eq!(
+            a.to_dot(&[2]),
+            "digraph heap {\n    node [shape=circle];\n    n0 [label=\"3\"];\n    n1 [label=\"2\"];\n    n2 [label=\"1\", style=filled, fillcolor=lightcoral];\n    n0 -> n1;\n    n0 -> n2;\n}\n"
+        );
+        let b = PqCompleteHeap::new_with_vec(vec!["a\"b"]);
+        assert!(b.to_dot(&[]).contains(r#"n0 [label="\"a\\\"b\""];"#));
+    }
+
+    #[test]
+    fn test_display() {
+        let a: PqCompleteHeap<i32> = PqCompleteHeap::new();
+        assert_eq!(a.to_string(), "(empty)\n");
+        let b = PqCompleteHeap::new_with_vec(vec![1, 2, 3, 4, 5]);
+        assert_eq!(b.to_string(), "5\n├── 4\n│   ├── 1\n│   └── 2\n└── 3\n");
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut a = PqCompleteHeap::new_with_vec(vec![4, 2, 3, 5, 1]);
+        assert_eq!(a.validate(), Ok(()));
+        assert!(!a.repair());
+
+        *a.peek_mut().unwrap() = 0;
+        assert_eq!(a.validate(), Err(1));
+        assert!(a.repair());
+        assert_eq!(a.validate(), Ok(()));
+        assert_eq!(a.peek(), Some(&4));
+
+        let b: PqCompleteHeap<i32> = PqCompleteHeap::new();
+        assert_eq!(b.validate(), Ok(()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "heap property violated at index 1")]
+    fn test_debug_validate() {
+        let mut a = PqCompleteHeap::new_with_vec(vec![1, 2, 3]);
+        *a.peek_mut().unwrap() = 0;
+        a.push(-1);
+    }
+
+    #[test]
+    fn test_priority_map() {
+        let mut m = PriorityMap::new();
+        assert_eq!(m.pop(), None);
+        assert_eq!(m.insert("a", 3), None);
+        assert_eq!(m.insert("b", 5), None);
+        assert_eq!(m.insert("c", 1), None);
+        assert_eq!(m.peek(), Some((&"b", &5)));
+
+        assert_eq!(m.insert("c", 7), Some(1));
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.peek(), Some((&"c", &7)));
+
+        assert_eq!(m.push_increase("a", 2), Some(2));
+        assert_eq!(m.get_priority(&"a"), Some(&3));
+        assert_eq!(m.push_increase("a", 9), Some(3));
+        assert_eq!(m.push_increase("d", 4), None);
+
+        assert_eq!(m.change_priority(&"a", 0), Some(9));
+        assert_eq!(m.change_priority(&"z", 0), None);
+
+        assert_eq!(m.remove(&"b"), Some(("b", 5)));
+        assert_eq!(m.remove(&"b"), None);
+        assert!(!m.contains_key(&"b"));
+
+        assert_eq!(m.pop(), Some(("c", 7)));
+        assert_eq!(m.pop(), Some(("d", 4)));
+        assert_eq!(m.pop(), Some(("a", 0)));
+        assert_eq!(m.pop(), None);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_priority_map_random() {
+        let mut m = PriorityMap::new();
+        let mut reference = std::collections::HashMap::new();
+        let mut seed: u64 = 42;
+        for _ in 0..2000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let key = (seed >> 33) % 64;
+            let priority = (seed >> 20) % 1000;
+            match seed % 3 {
+                0 => assert_eq!(m.insert(key, priority), reference.insert(key, priority)),
+                1 => assert_eq!(m.remove(&key).map(|e| e.1), reference.remove(&key)),
+                _ => assert_eq!(m.change_priority(&key, priority), {
+                    reference
+                        .get_mut(&key)
+                        .map(|p| std::mem::replace(p, priority))
+                }),
+            }
+            assert_eq!(m.peek().map(|e| *e.1), reference.values().max().copied());
+        }
+        let mut last = u64::MAX;
+        while let Some((key, priority)) = m.pop() {
+            assert!(priority <= last);
+            assert_eq!(reference.remove(&key), Some(priority));
+            last = priority;
+        }
+        assert!(reference.is_empty());
+    }
+
+    #[test]
+    fn test_sim_order() {
+        let mut sim = Simulation::new(vec![], 0);
+        sim.schedule_at(2.0, |sim| sim.state_mut().push("b"));
+        sim.schedule_at(1.0, |sim| sim.state_mut().push("a"));
+        sim.schedule_at(2.0, |sim| sim.state_mut().push("c"));
+        let cancelled = sim.schedule_at(1.5, |sim| sim.state_mut().push("x"));
+        sim.schedule_at(3.0, |sim| {
+            sim.state_mut().push("d");
+            sim.schedule_in(0.5, |sim| sim.state_mut().push("e"));
+        });
+
+        assert!(sim.cancel(cancelled));
+        assert!(!sim.cancel(cancelled));
+        assert_eq!(sim.run_until(2.0), 3);
+        assert_eq!(sim.now(), 2.0);
+        assert_eq!(sim.run_until(10.0), 2);
+        assert_eq!(sim.now(), 10.0);
+        assert_eq!(sim.pending(), 0);
+        assert_eq!(sim.into_state(), vec!["a", "b", "c", "d", "e"]);
+
+        // a cancelled head must not let a later event fire early
+        let mut sim = Simulation::new(vec![], 0);
+        let cancelled = sim.schedule_at(1.0, |sim| sim.state_mut().push("x"));
+        sim.schedule_at(5.0, |sim| sim.state_mut().push("late"));
+        assert!(sim.cancel(cancelled));
+        assert_eq!(sim.run_until(2.0), 0);
+        assert_eq!(sim.now(), 2.0);
+        assert_eq!(sim.pending(), 1);
+        assert_eq!(sim.run_until(5.0), 1);
+        assert_eq!(sim.now(), 5.0);
+        assert_eq!(sim.into_state(), vec!["late"]);
+    }
+
+    #[test]
+    fn test_sim_cancelled_timeouts() {
+        let mut sim = Simulation::new(0, 0);
+        for i in 0..10_000 {
+            sim.schedule_in(1.0, |sim| *sim.state_mut() += 1);
+            let timeout = sim.schedule_in(1e9 + i as f64, |sim| *sim.state_mut() += 100);
+            assert!(sim.cancel(timeout));
+            assert!(sim.queued() <= 2 * sim.pending());
+        }
+        assert_eq!(sim.pending(), 10_000);
+        assert_eq!(sim.run_until(f64::INFINITY), 10_000);
+        assert_eq!(sim.queued(), 0);
+        assert_eq!(sim.into_state(), 10_000);
+    }
+
+    #[test]
+    fn test_sim_mm1() {
+        let (arrival_rate, service_rate) = (0.5, 1.0);
+        let report = mm1(arrival_rate, service_rate, 200_000.0, 7);
+        let rho = arrival_rate / service_rate;
+        assert!((report.utilization - rho).abs() < 0.02);
+        assert!((report.mean_in_system - rho / (1.0 - rho)).abs() < 0.05);
+        assert!((report.mean_sojourn - 1.0 / (service_rate - arrival_rate)).abs() < 0.1);
+
+        let again = mm1(arrival_rate, service_rate, 200_000.0, 7);
+        assert_eq!(again.completed, report.completed);
+        assert_eq!(again.mean_sojourn, report.mean_sojourn);
+    }
+
+    fn blocked_executor(aging: Option<u64>) -> (PriorityExecutor, std::sync::mpsc::Sender<()>) {
+        let executor = PriorityExecutor::with_aging(1, aging);
+        let (gate, wait) = std::sync::mpsc::channel::<()>();
+        let (started, running) = std::sync::mpsc::channel();
+        executor.spawn(i64::MAX, move || {
+            started.send(()).unwrap();
+            wait.recv().unwrap();
+        });
+        running.recv().unwrap();
+        (executor, gate)
+    }
+
+    #[test]
+    fn test_executor_priority() {
+        let (executor, gate) = blocked_executor(None);
+        let order = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let handles: Vec<_> = [1, 5, 3, 5, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(i, priority)| {
+                let order = std::sync::Arc::clone(&order);
+                executor.spawn(priority, move || {
+                    order.lock().unwrap().push(i);
+                    i * 10
+                })
+            })
+            .collect();
+        assert_eq!(executor.pending(), 5);
+        gate.send(()).unwrap();
+
+        let results: Vec<_> = handles.into_iter().map(|h| h.join()).collect();
+        assert_eq!(results, vec![Ok(0), Ok(10), Ok(20), Ok(30), Ok(40)]);
+        assert_eq!(*order.lock().unwrap(), vec![1, 3, 2, 4, 0]);
+        executor.shutdown(Shutdown::Drain);
+    }
+
+    #[test]
+    fn test_executor_aging() {
+        let (executor, gate) = blocked_executor(Some(2));
+        let order = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let mut handles = vec![];
+        for (name, priority) in [("old", 0), ("a", 1), ("b", 1), ("c", 1), ("d", 1)] {
+            let order = std::sync::Arc::clone(&order);
+            handles.push(executor.spawn(priority, move || order.lock().unwrap().push(name)));
+        }
+        gate.send(()).unwrap();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(*order.lock().unwrap(), vec!["a", "old", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_executor_shutdown() {
+        let (executor, gate) = blocked_executor(None);
+        let cancelled = executor.spawn(0, || 1);
+        gate.send(()).unwrap();
+        executor.shutdown(Shutdown::Cancel);
+        assert!(matches!(
+            cancelled.join(),
+            Ok(1) | Err(JoinError::Cancelled)
+        ));
+
+        let (executor, gate) = blocked_executor(None);
+        let handles: Vec<_> = (0..10).map(|i| executor.spawn(i, move || i)).collect();
+        // the worker stays blocked until every pending job has been cancelled
+        let releaser = std::thread::spawn(move || {
+            for h in handles {
+                assert_eq!(h.join(), Err(JoinError::Cancelled));
+            }
+            gate.send(()).unwrap();
+        });
+        executor.shutdown(Shutdown::Cancel);
+        releaser.join().unwrap();
+
+        let executor = PriorityExecutor::new(4);
+        let panicked = executor.spawn(0, || panic!("boom"));
+        let handles: Vec<_> = (0..100).map(|i| executor.spawn(i, move || i * i)).collect();
+        drop(executor);
+        assert_eq!(panicked.join(), Err(JoinError::Panicked));
+        for (i, h) in handles.into_iter().enumerate() {
+            assert_eq!(h.join(), Ok((i * i) as i64));
+        }
+    }
+
+    #[test]
+    fn test_lazy_remove() {
+        let mut a = PqCompleteHeap::new_with_vec(vec![5, 3, 8, 3, 1]);
+        a.remove(&3);
+        assert_eq!(a.len(), 4);
+        a.remove(&8);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.peek(), Some(&5));
+        a.push(3);
+        a.remove(&5);
+        assert_eq!(a.peek(), Some(&3));
+        assert_eq!(a.pop(), Some(3));
+        assert_eq!(a.pop(), Some(3));
+        assert_eq!(a.pop(), Some(1));
+        assert_eq!(a.pop(), None);
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn test_lazy_remove_guards() {
+        let mut a = PqCompleteHeap::new_with_vec(vec![5, 3]);
+        assert!(a.remove(&3));
+        assert!(!a.remove(&3));
+        assert!(!a.remove(&7));
+        assert!(!a.remove(&4));
+        assert_eq!(a.len(), 1);
+        assert_eq!(a.peek(), Some(&5));
+
+        let mut stats = Stats::new();
+        let mut b = PqCompleteHeap::new_with_vec_observed(vec![4, 9, 2], &mut stats);
+        assert!(b.remove(&9));
+        assert_eq!(b.peek(), Some(&4));
+        drop(b);
+        assert_eq!(stats.removes, 1);
+        assert_eq!(stats.pops, 0);
+    }
+
+    #[test]
+    fn test_lazy_remove_repair() {
+        let mut a = PqCompleteHeap::new_with_vec(vec![5, 3]);
+        assert!(a.remove(&3));
+        *a.peek_mut().unwrap() = 1;
+        assert!(a.repair());
+        assert_eq!(a.len(), 1);
+        assert_eq!(a.as_slice(), &[1]);
+        assert_eq!(a.pop(), Some(1));
+        assert_eq!(a.pop(), None);
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn test_lazy_remove_reference() {
+        let mut rng = Rng::new(5);
+        let mut a = PqCompleteHeap::new();
+        let mut reference: Vec<u64> = vec![];
+        for _ in 0..3000 {
+            let value = rng.next_u64() % 40;
+            match rng.next_u64() % 5 {
+                0 | 1 => {
+                    a.push(value);
+                    reference.push(value);
+                }
+                2 => {
+                    let expected = reference.iter().position(|&x| x == value);
+                    assert_eq!(a.remove(&value), expected.is_some());
+                    if let Some(i) = expected {
+                        reference.swap_remove(i);
+                    }
+                }
+                3 if !reference.is_empty() => {
+                    let max = reference.iter().copied().max().unwrap();
+                    let i = reference.iter().position(|&x| x == max).unwrap();
+                    reference[i] = value;
+                    *a.peek_mut().unwrap() = value;
+                    a.repair();
+                }
+                _ => {
+                    let expected = reference.iter().copied().max();
+                    if let Some(max) = expected {
+                        let i = reference.iter().position(|&x| x == max).unwrap();
+                        reference.swap_remove(i);
+                    }
+                    assert_eq!(a.pop(), expected);
+                }
+            }
+            assert_eq!(a.len(), reference.len());
+            assert_eq!(a.peek(), reference.iter().max());
+        }
+    }
+
+    #[test]
+    fn test_windowed_extreme() {
+        let mut rng = Rng::new(11);
+        let samples: Vec<(u64, u64)> = (0..500)
+            .scan(0, |t, _| {
+                *t += rng.next_u64() % 4;
+                Some((rng.next_u64() % 100, *t))
+            })
+            .collect();
+
+        let mut by_count = WindowedExtreme::last_n(16);
+        let mut by_span = WindowedExtreme::last_span(10);
+        let mut min_by_count = WindowedExtreme::last_n(16);
+        for (i, &(value, t)) in samples.iter().enumerate() {
+            by_count.push(value, t);
+            by_span.push(value, t);
+            min_by_count.push(Reverse(value), t);
+
+            let last_n = &samples[i.saturating_sub(15)..=i];
+            assert_eq!(by_count.peek(), last_n.iter().map(|s| &s.0).max());
+            assert_eq!(
+                min_by_count.peek().map(|r| r.0),
+                last_n.iter().map(|s| s.0).min()
+            );
+            let expected = samples[..=i]
+                .iter()
+                .filter(|s| s.1 + 10 > t)
+                .map(|s| &s.0)
+                .max();
+            assert_eq!(by_span.peek(), expected);
+        }
+
+        let end = samples.last().unwrap().1;
+        assert_eq!(by_span.peek_at(end + 10), None);
+        assert!(by_count.peek_at(end + 10).is_some());
+    }
+
+    #[test]
+    fn test_windowed_extreme_limits() {
+        let mut forever = WindowedExtreme::last_span(u64::MAX);
+        forever.push(3, u64::MAX - 1);
+        forever.push(1, u64::MAX);
+        assert_eq!(forever.peek(), Some(&3));
+
+        let mut everything = WindowedExtreme::last_n(usize::MAX);
+        everything.push(7, 0);
+        everything.push(2, u64::MAX);
+        assert_eq!(everything.peek(), Some(&7));
+
+        let mut instant = WindowedExtreme::last_span(0);
+        instant.push(5, u64::MAX);
+        assert_eq!(instant.peek(), None);
+        let mut nothing = WindowedExtreme::last_n(0);
+        nothing.push(5, 0);
+        assert_eq!(nothing.peek(), None);
+    }
+
+    #[test]
+    fn test_new_with_vec_parallel() {
+        let mut rng = Rng::new(3);
+        for n in [0, 1, 2, 7, 8, 100, 1023, 1024, 5000, 100_000] {
+            let data: Vec<u64> = (0..n).map(|_| rng.next_u64() % 1000).collect();
+            let sequential = PqCompleteHeap::new_with_vec(data.clone());
+            for threads in [1, 2, 3, 4, 8] {
+                let parallel = PqCompleteHeap::new_with_vec_parallel(data.clone(), threads);
+                assert_eq!(parallel.validate(), Ok(()));
+                assert_eq!(parallel.as_slice(), sequential.as_slice());
+            }
+        }
+    }
+
+    struct StrCodec;
+
+    impl HeapCodec<String> for StrCodec {
+        fn encode(&self, value: &String, out: &mut Vec<u8>) {
+            out.extend_from_slice(value.as_bytes());
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Option<String> {
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let words = ["pear", "apple", "fig", "kiwi", "banana", "", "plum"];
+        let mut a = PqCompleteHeap::new_with_vec(words.iter().map(|w| w.to_string()).collect());
+        let mut bytes = vec![];
+        a.save_to(&mut bytes, &StrCodec).unwrap();
+        let b = PqCompleteHeap::load_from(&bytes[..], &StrCodec).unwrap();
+        assert_eq!(b.as_slice(), a.as_slice());
+
+        a.remove(&"kiwi".to_string());
+        a.remove(&"plum".to_string());
+        bytes.clear();
+        a.save_to(&mut bytes, &StrCodec).unwrap();
+        let mut b = PqCompleteHeap::load_from(&bytes[..], &StrCodec).unwrap();
+        assert_eq!(b.len(), 5);
+        for expected in ["pear", "fig", "banana", "apple", ""] {
+            assert_eq!(b.pop().as_deref(), Some(expected));
+        }
+
+        let empty: PqCompleteHeap<String> = PqCompleteHeap::new();
+        bytes.clear();
+        empty.save_to(&mut bytes, &StrCodec).unwrap();
+        assert!(PqCompleteHeap::load_from(&bytes[..], &StrCodec)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_errors() {
+        let a = PqCompleteHeap::new_with_vec(vec!["a".to_string(), "bc".to_string()]);
+        let mut bytes = vec![];
+        a.save_to(&mut bytes, &StrCodec).unwrap();
+
+        for cut in 0..bytes.len() {
+            assert!(matches!(
+                PqCompleteHeap::load_from(&bytes[..cut], &StrCodec),
+                Err(SnapshotError::Truncated)
+            ));
+        }
+
+        let mut corrupt = bytes.clone();
+        corrupt[0] = b'X';
+        assert!(matches!(
+            PqCompleteHeap::load_from(&corrupt[..], &StrCodec),
+            Err(SnapshotError::BadMagic)
+        ));
+
+        let mut corrupt = bytes.clone();
+        corrupt[4] = 9;
+        assert!(matches!(
+            PqCompleteHeap::load_from(&corrupt[..], &StrCodec),
+            Err(SnapshotError::UnsupportedVersion(9))
+        ));
+
+        let mut corrupt = bytes.clone();
+        let n = corrupt.len();
+        corrupt[n - 9] = b'z';
+        assert!(matches!(
+            PqCompleteHeap::load_from(&corrupt[..], &StrCodec),
+            Err(SnapshotError::ChecksumMismatch { .. })
+        ));
+
+        let mut invalid = PqCompleteHeap::new();
+        invalid.push(vec![0xff_u8]);
+        struct BytesCodec;
+        impl HeapCodec<Vec<u8>> for BytesCodec {
+            fn encode(&self, value: &Vec<u8>, out: &mut Vec<u8>) {
+                out.extend_from_slice(value);
+            }
+            fn decode(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+                Some(bytes.to_vec())
+            }
+        }
+        bytes.clear();
+        invalid.save_to(&mut bytes, &BytesCodec).unwrap();
+        assert!(matches!(
+            PqCompleteHeap::load_from(&bytes[..], &StrCodec),
+            Err(SnapshotError::Element(0))
+        ));
+
+        // an undecodable element in a corrupt snapshot is blamed on corruption
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(matches!(
+            PqCompleteHeap::load_from(&bytes[..], &StrCodec),
+            Err(SnapshotError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unique_heap() {
+        let mut a = UniqueHeap::new();
+        assert!(a.push(3));
+        assert!(a.push(1));
+        assert!(!a.push(3));
+        assert!(a.push(2));
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(&1));
+        assert_eq!(a.pop(), Some(3));
+        assert!(!a.contains(&3));
+        assert!(a.push(3));
+        assert_eq!(a.pop(), Some(3));
+        assert_eq!(a.pop(), Some(2));
+        assert_eq!(a.pop(), Some(1));
+        assert_eq!(a.pop(), None);
+    }
+
+    #[test]
+    fn test_unique_heap_replace() {
+        #[derive(Debug)]
+        struct Url(&'static str, u32);
+
+        impl PartialEq for Url {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl Eq for Url {}
+
+        impl std::hash::Hash for Url {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0.hash(state);
+            }
+        }
+
+        impl PartialOrd for Url {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Url {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.1.cmp(&other.1)
+            }
+        }
+
+        let mut frontier = UniqueHeap::new();
+        assert!(frontier.push(Url("a.com", 5)));
+        assert!(frontier.push(Url("b.com", 3)));
+        assert!(!frontier.push(Url("b.com", 9)));
+        assert!(!frontier.push_increase(Url("a.com", 1)));
+        assert!(frontier.push_increase(Url("b.com", 9)));
+        assert!(frontier.push_increase(Url("c.com", 7)));
+        assert_eq!(frontier.len(), 3);
+
+        let order: Vec<_> = std::iter::from_fn(|| frontier.pop())
+            .map(|u| (u.0, u.1))
+            .collect();
+        assert_eq!(order, vec![("b.com", 9), ("c.com", 7), ("a.com", 5)]);
+        assert!(frontier.is_empty());
+    }
+
+    #[test]
+    fn test_fair_queue_weights() {
+        let mut q = FairQueue::new();
+        q.set_weight("a", 1.0);
+        q.set_weight("b", 2.0);
+        q.set_weight("c", 3.0);
+        for i in 0..3000 {
+            for flow in ["a", "b", "c"] {
+                q.enqueue(flow, 1.0, i);
+            }
+        }
+        assert_eq!(q.len(), 9000);
+
+        let mut served = std::collections::HashMap::new();
+        for _ in 0..6000 {
+            let (flow, _) = q.dequeue().unwrap();
+            *served.entry(flow).or_insert(0.0_f64) += 1.0;
+        }
+        assert!((served["b"] / served["a"] - 2.0).abs() < 0.01);
+        assert!((served["c"] / served["a"] - 3.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fair_queue_cost() {
+        let mut q = FairQueue::new();
+        for i in 0..1000 {
+            q.enqueue(0, 1.0, i);
+            q.enqueue(1, 4.0, i);
+        }
+        let mut work = [0.0_f64; 2];
+        let mut next = [0, 0];
+        for _ in 0..1000 {
+            let (flow, item) = q.dequeue().unwrap();
+            assert_eq!(item, next[flow]);
+            next[flow] += 1;
+            work[flow] += if flow == 0 { 1.0 } else { 4.0 };
+        }
+        assert!((work[0] / work[1] - 1.0).abs() < 0.01);
+
+        // a newly active flow starts from the current virtual time
+        q.enqueue(2, 1.0, 0);
+        let position = std::iter::from_fn(|| q.dequeue()).position(|(flow, _)| flow == 2);
+        assert!(position.unwrap() < 3);
+        while q.dequeue().is_some() {}
+        assert!(q.is_empty());
+    }
 }
@@ -0,0 +1,133 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::pq_complete_heap::PqCompleteHeap;
+
+// Each pushed item gets a fresh id, so a replaced entry can be removed
+// lazily without relying on `Ord` agreeing with `Eq` for `T`.
+#[derive(Debug)]
+struct Entry<T> {
+    item: Arc<T>,
+    id: u64,
+}
+
+impl<T> Clone for Entry<T> {
+    fn clone(&self) -> Self {
+        Entry {
+            item: Arc::clone(&self.item),
+            id: self.id,
+        }
+    }
+}
+
+impl<T: Ord> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Entry<T> {}
+
+impl<T: Ord> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.item
+            .cmp(&other.item)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+#[derive(Debug)]
+pub struct UniqueHeap<T>
+where
+    T: Ord + Hash,
+{
+    heap: PqCompleteHeap<Entry<T>>,
+    members: HashMap<Arc<T>, u64>,
+    next_id: u64,
+}
+
+impl<T> UniqueHeap<T>
+where
+    T: Ord + Hash,
+{
+    pub fn new() -> Self {
+        UniqueHeap {
+            heap: PqCompleteHeap::new(),
+            members: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn push(&mut self, e: T) -> bool {
+        if self.members.contains_key(&e) {
+            return false;
+        }
+        self.insert(e);
+        true
+    }
+
+    pub fn push_increase(&mut self, e: T) -> bool {
+        let (old, id) = match self.members.get_key_value(&e) {
+            None => {
+                self.insert(e);
+                return true;
+            }
+            Some((old, _)) if **old >= e => return false,
+            Some((old, &id)) => (Arc::clone(old), id),
+        };
+        self.members.remove(&e);
+        self.heap.remove(&Entry { item: old, id });
+        self.insert(e);
+        true
+    }
+
+    pub fn contains(&self, e: &T) -> bool {
+        self.members.contains_key(e)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|entry| &*entry.item)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let entry = self.heap.pop()?;
+        self.members.remove(&entry.item);
+        match Arc::try_unwrap(entry.item) {
+            Ok(item) => Some(item),
+            Err(_) => unreachable!("popped entry is shared with a tombstone"),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    fn insert(&mut self, e: T) {
+        let item = Arc::new(e);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.members.insert(Arc::clone(&item), id);
+        self.heap.push(Entry { item, id });
+    }
+}
+
+impl<T> Default for UniqueHeap<T>
+where
+    T: Ord + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
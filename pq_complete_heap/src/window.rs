@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+
+use crate::pq_complete_heap::PqCompleteHeap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Count(usize),
+    Span(u64),
+}
+
+#[derive(Debug)]
+struct Sample<T> {
+    value: T,
+    timestamp: u64,
+    seq: u64,
+}
+
+impl<T: Ord> PartialEq for Sample<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for Sample<T> {}
+
+impl<T: Ord> PartialOrd for Sample<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Sample<T> {
+    // among equal values the newest sample sits on top, so it expires last
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value
+            .cmp(&other.value)
+            .then_with(|| self.seq.cmp(&other.seq))
+    }
+}
+
+#[derive(Debug)]
+pub struct WindowedExtreme<T>
+where
+    T: Ord,
+{
+    heap: PqCompleteHeap<Sample<T>>,
+    window: Window,
+    seq: u64,
+    now: u64,
+}
+
+impl<T> WindowedExtreme<T>
+where
+    T: Ord,
+{
+    pub fn new(window: Window) -> Self {
+        WindowedExtreme {
+            heap: PqCompleteHeap::new(),
+            window,
+            seq: 0,
+            now: 0,
+        }
+    }
+
+    pub fn last_n(n: usize) -> Self {
+        Self::new(Window::Count(n))
+    }
+
+    pub fn last_span(span: u64) -> Self {
+        Self::new(Window::Span(span))
+    }
+
+    pub fn push(&mut self, value: T, timestamp: u64) {
+        self.now = self.now.max(timestamp);
+        self.heap.push(Sample {
+            value,
+            timestamp,
+            seq: self.seq,
+        });
+        self.seq += 1;
+    }
+
+    pub fn peek(&mut self) -> Option<&T> {
+        while let Some(top) = self.heap.peek() {
+            if !self.expired(top) {
+                break;
+            }
+            self.heap.pop();
+        }
+        self.heap.peek().map(|sample| &sample.value)
+    }
+
+    pub fn peek_at(&mut self, now: u64) -> Option<&T> {
+        self.now = self.now.max(now);
+        self.peek()
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn expired(&self, sample: &Sample<T>) -> bool {
+        match self.window {
+            // differences, since `now` and `seq` never fall behind a sample
+            Window::Count(n) => self.seq - sample.seq > n as u64,
+            Window::Span(span) => self.now - sample.timestamp >= span,
+        }
+    }
+}